@@ -1,7 +1,6 @@
 import tkinter as tk
-import matplotlib.cm as cm
 try:
-    from tkrender import Mesh
+    from tkrender import Camera, Colormap, Mesh, Scene
 except:
     raise ImportError(
         "tkrender not found. Please build it by running build.ps1 or install.ps1 in the project root directory")
@@ -28,38 +27,53 @@ class Engine:
         self.x = 0
         self.y = 0
         self.meshes = []
+        self.scene = Scene()
 
-        self.cmap = cm.get_cmap(cmap)
+        self.cmap = Colormap(cmap)
         self.ctx.bind("<B1-Motion>", self.__mouse_handler)
         self.ctx.bind("<Button-1>", self.__mouse_reset)
         self.root.bind("<Key>", self.__keyboard_handler)
         self.root.bind("<MouseWheel>", self.__scroll_handler)
         self.root.title("Press H for help")
 
-    def add_mesh(self, mesh):
+    def add_mesh(self, mesh, **transform):
         self.meshes.append(mesh)
+        self.scene.add(mesh, **transform)
+
+    def load_mesh(self, filename, **transform):
+        loader = Mesh.load_async(filename, self.__load_progress)
+        self.root.after(50, self.__poll_loader, loader, transform)
+
+    def __load_progress(self, bytes_parsed, faces_built):
+        self.root.title(
+            f"Loading... {bytes_parsed} bytes parsed, {faces_built} faces built")
+
+    def __poll_loader(self, loader, transform):
+        if not loader.poll():
+            self.root.after(50, self.__poll_loader, loader, transform)
+            return
+        self.add_mesh(loader.result(), **transform)
+        self.root.title("Press H for help")
+        self.redraw()
 
     def redraw(self):
         self.ctx.delete("all")
-        for mesh in self.meshes:
-            if self.wireframe:
-                self.__draw_wireframe(mesh)
-            else:
-                self.__draw_shaded(mesh)
-
-    def __draw_shaded(self, mesh):
-        for poly, shade in mesh.get_shaded(self.focal, self.origin, self.disable_culling, self.disable_occlusion):
-            color = self.cmap(shade)
-            polygon = [
-                *map(lambda x: self.__project(x[0], x[1]), poly)]
-            self.ctx.create_polygon(
-                polygon, fill=f"#{int(255*color[0]):02x}{int(255*color[1]):02x}{int(255*color[2]):02x}", outline="")
-
-    def __draw_wireframe(self, mesh):
-        for poly in mesh.get_view(self.focal, self.origin, self.disable_culling):
-            polygon = [
-                *map(lambda x: self.__project(x[0], x[1]), poly)]
-            self.ctx.create_polygon(polygon, fill="", outline="#ffffff")
+        if self.wireframe:
+            self.__draw_wireframe()
+        else:
+            self.__draw_shaded()
+
+    def __draw_shaded(self):
+        camera = Camera(self.focal, self.origin, self.range)
+        script = self.scene.render_canvas(camera, self.width, self.height, canvas=str(self.ctx),
+                                          disable_culling=self.disable_culling, disable_occlusion=self.disable_occlusion, colormap=self.cmap)
+        self.root.tk.eval(script)
+
+    def __draw_wireframe(self):
+        camera = Camera(self.focal, self.origin, self.range)
+        culling = "none" if self.disable_culling else "back"
+        for start, end in self.scene.get_edges(camera, culling, remove_hidden=not self.disable_occlusion):
+            self.ctx.create_line(*self.__project(start[0], start[1]), *self.__project(end[0], end[1]), fill="#ffffff")
 
     def run(self):
         self.ctx.pack()
@@ -117,5 +131,5 @@ class Engine:
 
 
 engine = Engine(width=DIM, height=DIM)
-engine.add_mesh(Mesh(FILENAME))
+engine.load_mesh(FILENAME)
 engine.run()
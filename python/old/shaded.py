@@ -15,7 +15,7 @@ def project(x, y, width, height, range):
 def main():
     ctx.delete("all")
     cmap = cm.get_cmap("inferno")
-    for poly, shade in object.get_shaded(focal, origin, DISABLE_CULLING):
+    for poly, shade in object.get_shaded(focal, origin, disable_culling=DISABLE_CULLING):
         color = cmap(shade)
         polygon = [
             *map(lambda x: project(x[0], x[1], WIDTH, HEIGHT, RANGE), poly)]
@@ -1,10 +1,48 @@
+from typing import Any, Callable
+
 class Mesh:
     """
     A class representing a 3D mesh object.
+    Materials come from the .mtl files the .obj references, with map_Kd textures
+    and texture coordinates used by render_image. A missing .mtl file leaves the faces
+    with the default material and a missing texture leaves its material untextured.
     
     :param path: The path to the .obj file to load.
+        Raises ValueError naming the line when the file is malformed.
+    :param precision: "double" or "single". Single precision geometry takes about a third less memory,
+        every polygon keeps its own lists of points, normals and texture coordinates and those don't shrink.
+        It gets rotated, culled, placed in a scene and moved into view in single precision, only lighting
+        and sorting widen each point to double precision as it's needed. A scene mixing both precisions
+        places everything in double precision.
     """
-    def __init__(self, path: str) -> None: ...
+    def __init__(self, path: str, precision: str = "double") -> None: ...
+    """
+    Whether the geometry is stored in "double" or "single" precision.
+    """
+    precision: str
+    """
+    Starts loading a mesh on a background thread and returns right away,
+    so a UI can keep running and poll the loader, e.g. from Tk's after().
+
+    :param path: The path to the .obj file to load.
+    :param progress: Called with the bytes parsed and the faces built so far whenever they change,
+        always from the thread calling poll or result, never from the background thread.
+    :param precision: "double" or "single", like for the constructor.
+    :return: A loader to poll, cancel or take the mesh from.
+    """
+    @staticmethod
+    def load_async(path: str, progress: Callable[[int, int], Any] | None = None, precision: str = "double") -> MeshLoader: ...
+    """
+    The material the mesh gets shaded with, a plain white diffuse material by default.
+    Reading it gives the first material of the mesh, setting it replaces all of them.
+    """
+    material: Material
+    """
+    Builds a BSP tree over the polygons of the mesh, splitting them where needed.
+    The tree follows rotate_in_place, so it only has to be built once for a static mesh,
+    after which get_shaded with visibility="bsp" orders the polygons without sorting.
+    """
+    def build_bsp(self) -> None: ...
     """
     Rotate the mesh in place.
     
@@ -25,22 +63,421 @@ class Mesh:
     """
     Gets a list of all of the polygons in the mesh.
     Backface culling is enabled by default but can be disabled by setting disable_culling to True.
+    A polygon is front facing when its normal points back towards the camera origin.
     
     :param focal: The focal point of the camera.
     :param origin: The origin of the camera.
-    :param disable_culling: Whether or not to disable backface culling.
+    :param disable_culling: Whether or not to disable backface culling, same as culling="none".
+    :param culling: Which faces to cull, one of "back", "front" or "none".
     :return: A list of all of the polygons in the mesh.
     """
-    def get_view(self, focal: list[float], origin: list[float], disable_culling: bool = False) -> list[list[list[float]]]: ...
+    def get_view(self, focal: list[float], origin: list[float], disable_culling: bool = False, culling: str = "back") -> list[list[list[float]]]: ...
+    """
+    Gets every edge of the mesh once as a projected line segment, even when polygons share it.
+    Without any filters all edges of polygons that aren't culled are returned,
+    with filters only the edges matching at least one of them.
+
+    :param camera: The camera to look from.
+    :param culling: Which faces to cull, one of "back", "front" or "none". Edges are kept while any of their polygons is.
+    :param boundary: Keep edges that only belong to a single polygon, where the surface ends.
+    :param crease: Keep edges where the normals of the two polygons differ by more than this angle in radians.
+    :param silhouette: Keep edges between a polygon facing the camera and one facing away, the outline of the mesh.
+    :param remove_hidden: Only return the parts of edges no polygon covers, split where they pass behind something. Culled polygons don't hide anything.
+    :return: A list of line segments, each a pair of points like those of get_shaded.
+    """
+    def get_edges(self, camera: Camera, culling: str = "back", boundary: bool = False, crease: float | None = None, silhouette: bool = False, remove_hidden: bool = False) -> list[list[list[float]]]: ...
     """
     Gets a list of tuples containing the polygons in the mesh and their respective shading.
     Just like with get_view backface culling is enabled by default but can be disabled by setting disable_culling to True.
+    The shading arguments are keyword only and every render method takes them the same way,
+    the rasterizing ones all but disable_culling, disable_occlusion, sort and visibility.
     
     :param focal: The focal point of the camera.
     :param origin: The origin of the camera.
-    :param disable_culling: Whether or not to disable backface culling.
+    :param disable_culling: Whether or not to disable backface culling, same as culling="none".
     :param disable_occlusion: Whether or not to disable occlusion culling.
+    :param culling: Which faces to cull, one of "back", "front" or "none".
+    :param sort: Which view space depth of each polygon to sort back to front by, one of "centroid", "max" or "min".
+    :param visibility: How to order the polygons, "sort" for a plain depth sort, "newell" to run the
+        Newell-Newell-Sancha overlap tests, splitting polygons where needed and ordering any cycle the splits
+        can't untangle with a BSP tree, so the order always paints correctly,
+        or "bsp" to traverse the tree made by build_bsp.
+    :param light: The light to shade with, defaults to a white light shining down the z axis.
+    :param lights: More lights to shade with, their contributions get added up.
+    :param shading: Where to evaluate the lights, "flat" at the centroid of each polygon or
+        "gouraud" at its vertices using smoothed vertex normals, averaging the results,
+        or "toon" for cel shading with the light quantized into bands.
+    :param material: The material to shade with instead of the one of the mesh.
+    :param fog: Fog to blend the colors towards with view space depth.
+    :param toon: The bands and outline to use with shading="toon".
+    :param shadows: Whether to cast a ray towards every light from each shaded point, dropping the light when
+        something is in the way. Slow, it builds a bounding volume hierarchy over all polygons on every call.
+    :param output: What to return the shading as, "shade" for its brightness between 0 and 1, "rgb" for a color tuple
+        or "hex" for a "#rrggbb" string ready to be used as a Tk color.
+    :param colormap: A colormap to run the brightness through before returning it as "rgb" or "hex".
     :return: A list of tuples containing the polygons in the mesh and their respective shading.
     """
-    def get_shaded(self, focal: list[float], origin: list[float], disable_culling: bool = False, disable_occlusion: bool = False) -> list[tuple[list[list[float]], float]]: ...
-    
\ No newline at end of file
+    def get_shaded(self, focal: list[float], origin: list[float], output: str = "shade", *, disable_culling: bool = False, disable_occlusion: bool = False, culling: str = "back", sort: str = "centroid", visibility: str = "sort", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> list[tuple[list[list[float]], Any]]: ...
+    """
+    Renders the mesh into an image with a depth buffer, which handles intersecting
+    and cyclically overlapping polygons that no painter's order can.
+    Gouraud shading interpolates the vertex colors across each polygon instead of averaging them.
+    The shading arguments work the same as for get_shaded.
+
+    :param camera: The camera to render from.
+    :param width: The width of the image in pixels.
+    :param height: The height of the image in pixels.
+    :param background: The color of pixels no polygon covers, with channels between 0 and 1.
+        Like antialias, filter and sampling it's keyword only and works the same for every method rendering images.
+    :param antialias: Renders at this many times the width and height and filters it back down,
+        2 takes 4 samples per pixel and 4 takes 16. 1 turns antialiasing off.
+    :param filter: How samples get filtered down, "box" averages the samples within each pixel,
+        "tent" also blends in neighbouring samples for softer edges at a bit more cost.
+    :param sampling: How textures get sampled, "nearest" for blocky texels or "bilinear" to blend them.
+    :return: The image as RGB bytes, row by row from the top left.
+    """
+    def render_image(self, camera: Camera, width: int, height: int, *, background: list[float] = [0, 0, 0], antialias: int = 1, filter: str = "box", sampling: str = "bilinear", culling: str = "back", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> bytes: ...
+    """
+    Renders the mesh like render_image, in a format a Tk photo image takes directly,
+    so the whole image gets shown with one photo.put(data), or photo.configure(data=data) for "ppm".
+
+    :param format: "rows" for a string of rows of colors, {#rrggbb #rrggbb ...} {...}, which only photo.put takes,
+        or "ppm" for the bytes of a binary PPM image, which photo.configure(data=data) takes as well
+        since the data option only accepts image formats.
+    :return: The image data.
+    """
+    def render_photo_data(self, camera: Camera, width: int, height: int, format: str = "rows", *, background: list[float] = [0, 0, 0], antialias: int = 1, filter: str = "box", sampling: str = "bilinear", culling: str = "back", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> str | bytes: ...
+    """
+    Renders the mesh like render_image into text for terminals without Tk, e.g. over SSH.
+    Characters are about twice as tall as wide, which the image allows for so shapes keep their proportions:
+    the camera range spans the columns and as much of it as fits the rows.
+
+    :param camera: The camera to render from.
+    :param columns: The number of characters per line.
+    :param rows: The number of lines.
+    :param mode: "ascii" picks a character from the ramp by brightness, "blocks" draws two pixels
+        per character with Unicode half blocks in 24-bit ANSI colors.
+    :param ramp: The characters for "ascii", from darkest to brightest.
+    :param background: The color of pixels no polygon covers in "blocks" mode,
+        the terminal's own background shows through if not given.
+    :return: The text, one line per row, ready to print.
+    """
+    def render_text(self, camera: Camera, columns: int, rows: int, mode: str = "ascii", ramp: str = ".,-~:;=!*#$@", background: list[float] | None = None, *, antialias: int = 1, filter: str = "box", sampling: str = "bilinear", culling: str = "back", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> str: ...
+    """
+    Renders the mesh like render_image and saves it, headless without Tk.
+    The format goes by the extension of the path.
+
+    :param path: Where to save the image, a .png or .ppm file.
+    :param alpha: Whether pixels no polygon covers are transparent instead of the background color.
+        Only PNG files have an alpha channel.
+    """
+    def render_to_file(self, path: str, camera: Camera, width: int, height: int, alpha: bool = False, *, background: list[float] = [0, 0, 0], antialias: int = 1, filter: str = "box", sampling: str = "bilinear", culling: str = "back", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> None: ...
+    """
+    Renders the mesh like get_shaded into an SVG document, one polygon per visible face in painter's order.
+    Points get mapped to pixels the same way as in render_image, so both line up.
+    The shading arguments work the same as for get_shaded.
+
+    :param camera: The camera to render from.
+    :param width: The width of the document in pixels.
+    :param height: The height of the document in pixels.
+    :param background: The color behind the polygons, transparent if not given.
+    :param stroke: The color to outline every polygon with, no outlines if not given.
+    :param stroke_width: The width of the outlines in pixels.
+    :return: The SVG document.
+    """
+    def render_svg(self, camera: Camera, width: int, height: int, background: list[float] | None = None, stroke: list[float] | None = None, stroke_width: float = 1.0, *, disable_culling: bool = False, disable_occlusion: bool = False, culling: str = "back", sort: str = "centroid", visibility: str = "sort", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> str: ...
+    """
+    Renders the mesh like get_shaded, ready to draw on a Tk canvas of the given size without any per-polygon work in Python.
+    Points get mapped to pixels the same way as in render_image.
+
+    :param camera: The camera to render from.
+    :param width: The width of the canvas in pixels.
+    :param height: The height of the canvas in pixels.
+    :param canvas: The Tk path name of the canvas, str(canvas) in tkinter. If given, a Tcl script
+        drawing every polygon gets returned, to run with canvas.tk.eval(script).
+    :param outline: The color to outline every polygon with, no outlines if not given.
+    :return: A list of tuples containing the flat coordinates of the polygons and their fill colors as "#rrggbb",
+        ready for canvas.create_polygon(coordinates, fill=fill), or the Tcl script if a canvas is given.
+    """
+    def render_canvas(self, camera: Camera, width: int, height: int, canvas: str | None = None, outline: list[float] | None = None, *, disable_culling: bool = False, disable_occlusion: bool = False, culling: str = "back", sort: str = "centroid", visibility: str = "sort", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> list[tuple[list[float], str]] | str: ...
+
+class MeshLoader:
+    """
+    A mesh being loaded in the background, returned by Mesh.load_async.
+    Loading gets cancelled when the loader is garbage collected before it finishes.
+    """
+    bytes_parsed: int
+    """
+    The size of the file, 0 until it has been read.
+    """
+    total_bytes: int
+    faces_built: int
+    """
+    Passes the progress on to the callback.
+
+    :return: Whether loading has finished, successfully or not.
+    """
+    def poll(self) -> bool: ...
+    """
+    Stops loading as soon as possible, result then raises a RuntimeError unless loading had already finished.
+    """
+    def cancel(self) -> None: ...
+    """
+    Waits for loading to finish and hands over the mesh. It can only be taken once.
+
+    :return: The loaded mesh, raising the error that stopped loading otherwise,
+        like the ValueError for a malformed file that the constructor raises.
+    """
+    def result(self) -> Mesh: ...
+
+class Light:
+    """
+    A directional light, like the sun. Surfaces get lit with Lambert's cosine law and
+    faces pointing away from the light stay dark.
+    Lights live in world space, so rotating a mesh in place turns it under a fixed light.
+    The colors of all lights reaching a surface get added up and clamped between 0 and 1.
+
+    :param direction: The direction the light shines in.
+    :param color: The color of the light, with channels between 0 and 1.
+    :param intensity: How bright the light is.
+    """
+    def __init__(self, direction: list[float] = [0, 0, 1], color: list[float] = [1, 1, 1], intensity: float = 1.0) -> None: ...
+    """
+    A light that reaches every surface equally, regardless of its orientation.
+    """
+    @staticmethod
+    def ambient(color: list[float] = [1, 1, 1], intensity: float = 1.0) -> Light: ...
+    """
+    Same as the constructor, a light shining in one direction everywhere.
+    """
+    @staticmethod
+    def directional(direction: list[float] = [0, 0, 1], color: list[float] = [1, 1, 1], intensity: float = 1.0) -> Light: ...
+    """
+    A light shining in all directions from a point, like a light bulb.
+
+    :param position: Where the light is.
+    :param attenuation: The constant, linear and quadratic falloff with distance d,
+        the light gets divided by constant + linear * d + quadratic * d**2.
+    """
+    @staticmethod
+    def point(position: list[float], color: list[float] = [1, 1, 1], intensity: float = 1.0, attenuation: list[float] = [1, 0, 1]) -> Light: ...
+    """
+    A point light limited to a cone, like a flashlight.
+
+    :param position: Where the light is.
+    :param direction: The direction the cone points in.
+    :param inner_angle: The angle from the axis of the cone in radians within which the light is at full strength.
+    :param outer_angle: The angle from the axis of the cone in radians where the light has faded out.
+    :param attenuation: The constant, linear and quadratic falloff with distance, same as for point lights.
+    """
+    @staticmethod
+    def spot(position: list[float], direction: list[float], inner_angle: float = 0.3, outer_angle: float = 0.5, color: list[float] = [1, 1, 1], intensity: float = 1.0, attenuation: list[float] = [1, 0, 1]) -> Light: ...
+
+class Material:
+    """
+    How a surface reflects light, using the Blinn-Phong reflection model.
+    Specular highlights depend on where the camera is, which makes shiny surfaces like metal read correctly.
+
+    :param diffuse: The color of the surface, with channels between 0 and 1.
+    :param specular: The color of the highlights, black to turn them off.
+    :param shininess: The specular exponent, higher values give smaller and sharper highlights.
+    :param texture: The path to a PNG or PPM image multiplying the diffuse color.
+        Only render_image maps textures, the other renderers ignore them.
+    :param wrap: What texture coordinates outside of 0 to 1 do, "repeat" tiles the texture
+        and "clamp" stretches its edges.
+    """
+    def __init__(self, diffuse: list[float] = [1, 1, 1], specular: list[float] = [0, 0, 0], shininess: float = 32.0, texture: str | None = None, wrap: str = "repeat") -> None: ...
+
+class Toon:
+    """
+    Settings for cel shading. The diffuse light gets quantized into evenly spaced bands from 0 to 1
+    and specular highlights become solid spots.
+
+    :param bands: How many bands to quantize into, with evenly spaced thresholds.
+    :param thresholds: The ascending light intensities where each next band starts, overrides bands.
+    :param outline: How far to push the silhouette polygons out along the vertex normals, 0 for no outline.
+        These are the back faces of the mesh, painted in the outline color behind the rest of it.
+    :param outline_color: The color of the outline.
+    """
+    def __init__(self, bands: int = 4, thresholds: list[float] | None = None, outline: float = 0.0, outline_color: list[float] = [0, 0, 0]) -> None: ...
+
+class Fog:
+    """
+    Depth cueing, blending colors towards the fog color the deeper they are in view space.
+
+    :param mode: How the fog thickens past start, "linear" until it's opaque at end,
+        "exp" for exp(-density * distance) or "exp2" for exp(-(density * distance)**2).
+    :param color: The color of the fog, usually the background color.
+    :param start: The view space depth where the fog begins.
+    :param end: The view space depth where linear fog becomes opaque.
+    :param density: How thick exponential fog is.
+    """
+    def __init__(self, mode: str = "linear", color: list[float] = [0, 0, 0], start: float = 0.0, end: float = 100.0, density: float = 0.05) -> None: ...
+
+class Colormap:
+    """
+    Maps a shade between 0 and 1 onto a color.
+
+    :param name: One of the built in perceptual colormaps "inferno", "viridis", "magma" or "grayscale".
+    """
+    def __init__(self, name: str = "inferno") -> None: ...
+    """
+    Makes a colormap out of a lookup table, shades in between two entries get linearly interpolated.
+
+    :param colors: The colors from a shade of 0 to 1, with channels between 0 and 1.
+        Raises ValueError for an empty list or a color without exactly 3 such channels.
+    """
+    @staticmethod
+    def from_lut(colors: list[list[float]]) -> Colormap: ...
+    """
+    :return: The color of a shade as a tuple with channels between 0 and 1.
+    """
+    def __call__(self, shade: float) -> tuple[float, float, float]: ...
+    """
+    :return: The color of a shade as a "#rrggbb" string.
+    """
+    def hex(self, shade: float) -> str: ...
+
+class Camera:
+    """
+    A pinhole camera looking down the z axis.
+
+    :param focal: The focal point of the camera.
+    :param origin: The origin of the camera.
+    :param range: How many units of the projected view span an image, same as the canvas in main.py.
+    """
+    focal: list[float]
+    origin: list[float]
+    range: float
+    def __init__(self, focal: list[float], origin: list[float], range: float = 20.0) -> None: ...
+
+class Scene:
+    """
+    A collection of meshes that get culled, shaded and depth sorted together.
+    Meshes are shared with Python, so rotating a mesh in place also rotates it in the scene.
+    """
+    def __init__(self) -> None: ...
+    """
+    Adds a mesh to the scene. The mesh is scaled first, then rotated and finally moved into place.
+
+    :param mesh: The mesh to add.
+    :param position: Where to move the mesh to.
+    :param rotation: The angles to rotate the mesh around the x, y and z axis in radians.
+    :param scale: The uniform scale of the mesh.
+    :return: The index of the mesh in the scene.
+    """
+    def add(self, mesh: Mesh, position: list[float] = [0, 0, 0], rotation: list[float] = [0, 0, 0], scale: float = 1.0) -> int: ...
+    """
+    Replaces the transform of a mesh in the scene.
+
+    :param index: The index returned by add.
+    :param position: Where to move the mesh to.
+    :param rotation: The angles to rotate the mesh around the x, y and z axis in radians.
+    :param scale: The uniform scale of the mesh.
+    """
+    def set_transform(self, index: int, position: list[float] = [0, 0, 0], rotation: list[float] = [0, 0, 0], scale: float = 1.0) -> None: ...
+    def __len__(self) -> int: ...
+    """
+    Gets every edge of the scene once as a projected line segment, even when polygons share it.
+    Without any filters all edges of polygons that aren't culled are returned,
+    with filters only the edges matching at least one of them.
+
+    :param camera: The camera to look from.
+    :param culling: Which faces to cull, one of "back", "front" or "none". Edges are kept while any of their polygons is.
+    :param boundary: Keep edges that only belong to a single polygon, where the surface ends.
+    :param crease: Keep edges where the normals of the two polygons differ by more than this angle in radians.
+    :param silhouette: Keep edges between a polygon facing the camera and one facing away, the outline of the scene.
+    :param remove_hidden: Only return the parts of edges no polygon covers, split where they pass behind something. Culled polygons don't hide anything.
+    :return: A list of line segments, each a pair of points like those of render.
+    """
+    def get_edges(self, camera: Camera, culling: str = "back", boundary: bool = False, crease: float | None = None, silhouette: bool = False, remove_hidden: bool = False) -> list[list[list[float]]]: ...
+    """
+    Same as Mesh.get_shaded, but for the polygons of every mesh in the scene at once.
+    The "bsp" visibility mode isn't available for scenes.
+
+    :param camera: The camera to render from.
+    :return: A list of tuples containing the polygons in the scene and their respective shading.
+    """
+    def render(self, camera: Camera, output: str = "shade", *, disable_culling: bool = False, disable_occlusion: bool = False, culling: str = "back", sort: str = "centroid", visibility: str = "sort", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> list[tuple[list[list[float]], Any]]: ...
+    """
+    Renders the scene into an image with a depth buffer, which handles intersecting
+    and cyclically overlapping polygons that no painter's order can.
+    Gouraud shading interpolates the vertex colors across each polygon instead of averaging them.
+    The shading arguments work the same as for render.
+
+    :param camera: The camera to render from.
+    :param width: The width of the image in pixels.
+    :param height: The height of the image in pixels.
+    :param background: The color of pixels no polygon covers, with channels between 0 and 1.
+        Like antialias, filter and sampling it's keyword only and works the same for every method rendering images.
+    :param antialias: Renders at this many times the width and height and filters it back down,
+        2 takes 4 samples per pixel and 4 takes 16. 1 turns antialiasing off.
+    :param filter: How samples get filtered down, "box" averages the samples within each pixel,
+        "tent" also blends in neighbouring samples for softer edges at a bit more cost.
+    :param sampling: How textures get sampled, "nearest" for blocky texels or "bilinear" to blend them.
+    :return: The image as RGB bytes, row by row from the top left.
+    """
+    def render_image(self, camera: Camera, width: int, height: int, *, background: list[float] = [0, 0, 0], antialias: int = 1, filter: str = "box", sampling: str = "bilinear", culling: str = "back", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> bytes: ...
+    """
+    Renders the scene like render_image, in a format a Tk photo image takes directly,
+    so the whole image gets shown with one photo.put(data), or photo.configure(data=data) for "ppm".
+
+    :param format: "rows" for a string of rows of colors, {#rrggbb #rrggbb ...} {...}, which only photo.put takes,
+        or "ppm" for the bytes of a binary PPM image, which photo.configure(data=data) takes as well
+        since the data option only accepts image formats.
+    :return: The image data.
+    """
+    def render_photo_data(self, camera: Camera, width: int, height: int, format: str = "rows", *, background: list[float] = [0, 0, 0], antialias: int = 1, filter: str = "box", sampling: str = "bilinear", culling: str = "back", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> str | bytes: ...
+    """
+    Renders the scene like render_image into text for terminals without Tk, e.g. over SSH.
+    Characters are about twice as tall as wide, which the image allows for so shapes keep their proportions:
+    the camera range spans the columns and as much of it as fits the rows.
+
+    :param camera: The camera to render from.
+    :param columns: The number of characters per line.
+    :param rows: The number of lines.
+    :param mode: "ascii" picks a character from the ramp by brightness, "blocks" draws two pixels
+        per character with Unicode half blocks in 24-bit ANSI colors.
+    :param ramp: The characters for "ascii", from darkest to brightest.
+    :param background: The color of pixels no polygon covers in "blocks" mode,
+        the terminal's own background shows through if not given.
+    :return: The text, one line per row, ready to print.
+    """
+    def render_text(self, camera: Camera, columns: int, rows: int, mode: str = "ascii", ramp: str = ".,-~:;=!*#$@", background: list[float] | None = None, *, antialias: int = 1, filter: str = "box", sampling: str = "bilinear", culling: str = "back", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> str: ...
+    """
+    Renders the scene like render_image and saves it, headless without Tk.
+    The format goes by the extension of the path.
+
+    :param path: Where to save the image, a .png or .ppm file.
+    :param alpha: Whether pixels no polygon covers are transparent instead of the background color.
+        Only PNG files have an alpha channel.
+    """
+    def render_to_file(self, path: str, camera: Camera, width: int, height: int, alpha: bool = False, *, background: list[float] = [0, 0, 0], antialias: int = 1, filter: str = "box", sampling: str = "bilinear", culling: str = "back", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> None: ...
+    """
+    Renders the scene like render into an SVG document, one polygon per visible face in painter's order.
+    Points get mapped to pixels the same way as in render_image, so both line up.
+    The shading arguments work the same as for render.
+
+    :param camera: The camera to render from.
+    :param width: The width of the document in pixels.
+    :param height: The height of the document in pixels.
+    :param background: The color behind the polygons, transparent if not given.
+    :param stroke: The color to outline every polygon with, no outlines if not given.
+    :param stroke_width: The width of the outlines in pixels.
+    :return: The SVG document.
+    """
+    def render_svg(self, camera: Camera, width: int, height: int, background: list[float] | None = None, stroke: list[float] | None = None, stroke_width: float = 1.0, *, disable_culling: bool = False, disable_occlusion: bool = False, culling: str = "back", sort: str = "centroid", visibility: str = "sort", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> str: ...
+    """
+    Renders the scene like render, ready to draw on a Tk canvas of the given size without any per-polygon work in Python.
+    Points get mapped to pixels the same way as in render_image.
+
+    :param camera: The camera to render from.
+    :param width: The width of the canvas in pixels.
+    :param height: The height of the canvas in pixels.
+    :param canvas: The Tk path name of the canvas, str(canvas) in tkinter. If given, a Tcl script
+        drawing every polygon gets returned, to run with canvas.tk.eval(script).
+    :param outline: The color to outline every polygon with, no outlines if not given.
+    :return: A list of tuples containing the flat coordinates of the polygons and their fill colors as "#rrggbb",
+        ready for canvas.create_polygon(coordinates, fill=fill), or the Tcl script if a canvas is given.
+    """
+    def render_canvas(self, camera: Camera, width: int, height: int, canvas: str | None = None, outline: list[float] | None = None, *, disable_culling: bool = False, disable_occlusion: bool = False, culling: str = "back", sort: str = "centroid", visibility: str = "sort", light: Light | None = None, lights: list[Light] | None = None, shading: str = "flat", material: Material | None = None, fog: Fog | None = None, toon: Toon | None = None, shadows: bool = False, colormap: Colormap | None = None) -> list[tuple[list[float], str]] | str: ...
@@ -0,0 +1,235 @@
+use crate::plane::{Plane, EPSILON};
+use nalgebra::{Matrix3, Vector3};
+
+/// How many polygons are tried as the splitting plane of each node.
+const CANDIDATES: usize = 8;
+
+struct Node {
+    plane: Plane,
+    polygons: Vec<(usize, Vec<Vector3<f64>>)>,
+    front: Option<usize>,
+    back: Option<usize>,
+}
+
+enum Side {
+    Front,
+    Back,
+    Coplanar,
+    Spanning,
+}
+
+fn classify(plane: &Plane, points: &[Vector3<f64>]) -> Side {
+    let mut front = false;
+    let mut back = false;
+    for point in points {
+        let distance = plane.distance(point);
+        front |= distance > EPSILON;
+        back |= distance < -EPSILON;
+    }
+    match (front, back) {
+        (true, true) => Side::Spanning,
+        (true, false) => Side::Front,
+        (false, true) => Side::Back,
+        (false, false) => Side::Coplanar,
+    }
+}
+
+/// Picks the polygon whose plane splits the fewest others while keeping both halves balanced,
+/// out of a handful of evenly spaced candidates.
+fn choose_splitter(polygons: &[(usize, Vec<Vector3<f64>>)]) -> Option<(usize, Plane)> {
+    let step = (polygons.len() / CANDIDATES).max(1);
+    polygons
+        .iter()
+        .enumerate()
+        .step_by(step)
+        .map(|(index, (_, points))| (index, Plane::new(points)))
+        .filter(|(_, plane)| plane.normal.norm_squared() > 0.0)
+        .min_by_key(|(_, plane)| {
+            let (mut front, mut back, mut spanning) = (0i64, 0i64, 0i64);
+            for (_, points) in polygons {
+                match classify(plane, points) {
+                    Side::Front => front += 1,
+                    Side::Back => back += 1,
+                    Side::Spanning => spanning += 1,
+                    Side::Coplanar => {}
+                }
+            }
+            8 * spanning + (front - back).abs()
+        })
+}
+
+/// Binary space partitioning tree over the polygons of a mesh, giving a back to front
+/// order for any camera position without sorting.
+pub struct Bsp {
+    nodes: Vec<Node>,
+}
+
+impl Bsp {
+    /// Builds the tree, splitting polygons that straddle a node's plane.
+    /// Every piece keeps the index of the polygon it was cut from.
+    pub fn new(polygons: Vec<(usize, Vec<Vector3<f64>>)>) -> Self {
+        let mut nodes: Vec<Node> = Vec::new();
+        // the tree is built iteratively, large meshes easily get deeper than the stack allows
+        let mut stack: Vec<(Option<(usize, bool)>, _)> = vec![(None, polygons)];
+        while let Some((parent, polygons)) = stack.pop() {
+            if polygons.is_empty() {
+                continue;
+            }
+            let index = nodes.len();
+            match parent {
+                Some((parent, true)) => nodes[parent].front = Some(index),
+                Some((parent, false)) => nodes[parent].back = Some(index),
+                None => {}
+            }
+            let Some((splitter, plane)) = choose_splitter(&polygons) else {
+                // only degenerate polygons left, any order paints them the same
+                nodes.push(Node {
+                    plane: Plane::new(&polygons[0].1),
+                    polygons,
+                    front: None,
+                    back: None,
+                });
+                continue;
+            };
+
+            let mut coplanar = Vec::new();
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+            for (i, (source, points)) in polygons.into_iter().enumerate() {
+                if i == splitter {
+                    // slivers can straddle their own plane through rounding, always keep them here
+                    coplanar.push((source, points));
+                    continue;
+                }
+                match classify(&plane, &points) {
+                    Side::Front => front.push((source, points)),
+                    Side::Back => back.push((source, points)),
+                    Side::Coplanar => coplanar.push((source, points)),
+                    Side::Spanning => {
+                        let (front_part, back_part) = plane.split(&points);
+                        if front_part.len() >= 3 {
+                            front.push((source, front_part));
+                        }
+                        if back_part.len() >= 3 {
+                            back.push((source, back_part));
+                        }
+                    }
+                }
+            }
+            nodes.push(Node {
+                plane,
+                polygons: coplanar,
+                front: None,
+                back: None,
+            });
+            stack.push((Some((index, true)), front));
+            stack.push((Some((index, false)), back));
+        }
+        Self { nodes }
+    }
+
+    pub fn transform_inplace(&mut self, matrix: Matrix3<f64>) {
+        // a rotation about the origin keeps the plane offsets intact
+        for node in self.nodes.iter_mut() {
+            node.plane.normal = matrix * node.plane.normal;
+            for (_, points) in node.polygons.iter_mut() {
+                for point in points.iter_mut() {
+                    *point = matrix * *point;
+                }
+            }
+        }
+    }
+
+    /// All pieces in back to front order as seen from `eye`.
+    pub fn order(&self, eye: &Vector3<f64>) -> Vec<&(usize, Vec<Vector3<f64>>)> {
+        enum Visit {
+            Node(usize),
+            Emit(usize),
+        }
+
+        let mut ordered = Vec::new();
+        let mut stack = Vec::new();
+        if !self.nodes.is_empty() {
+            stack.push(Visit::Node(0));
+        }
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Emit(index) => ordered.extend(self.nodes[index].polygons.iter()),
+                Visit::Node(index) => {
+                    let node = &self.nodes[index];
+                    let (near, far) = if node.plane.distance(eye) >= 0.0 {
+                        (node.front, node.back)
+                    } else {
+                        (node.back, node.front)
+                    };
+                    // pushed in reverse, the far side gets painted first
+                    stack.extend(near.map(Visit::Node));
+                    stack.push(Visit::Emit(index));
+                    stack.extend(far.map(Visit::Node));
+                }
+            }
+        }
+        ordered
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(ordered: &[&(usize, Vec<Vector3<f64>>)]) -> Vec<usize> {
+        ordered.iter().map(|(source, _)| *source).collect()
+    }
+
+    #[test]
+    fn orders_from_both_sides_of_a_plane() {
+        let triangle = |z: f64| {
+            vec![
+                Vector3::new(-1.0, -1.0, z),
+                Vector3::new(1.0, -1.0, z),
+                Vector3::new(0.0, 1.0, z),
+            ]
+        };
+        let bsp = Bsp::new(vec![(0, triangle(1.0)), (1, triangle(2.0))]);
+        assert_eq!(
+            sources(&bsp.order(&Vector3::new(0.0, 0.0, -5.0))),
+            vec![1, 0]
+        );
+        assert_eq!(
+            sources(&bsp.order(&Vector3::new(0.0, 0.0, 10.0))),
+            vec![0, 1]
+        );
+    }
+
+    #[test]
+    fn splits_interpenetrating_triangles() {
+        // tilted against each other so they cut through one another along y == 0
+        let bsp = Bsp::new(vec![
+            (
+                0,
+                vec![
+                    Vector3::new(-1.0, -1.0, 4.0),
+                    Vector3::new(1.0, -1.0, 4.0),
+                    Vector3::new(0.0, 1.0, 6.0),
+                ],
+            ),
+            (
+                1,
+                vec![
+                    Vector3::new(-1.0, 1.0, 4.0),
+                    Vector3::new(1.0, 1.0, 4.0),
+                    Vector3::new(0.0, -1.0, 6.0),
+                ],
+            ),
+        ]);
+        let ordered = bsp.order(&Vector3::zeros());
+        // the triangle that isn't the splitter gets cut in two, its far half painted
+        // before the other triangle and its near half after
+        let sources = sources(&ordered);
+        assert_eq!(sources.len(), 3);
+        assert_eq!(sources[0], sources[2]);
+        assert_ne!(sources[0], sources[1]);
+        assert!(ordered[0].1.iter().all(|point| point.z >= 5.0 - EPSILON));
+        assert!(ordered[2].1.iter().all(|point| point.z <= 5.0 + EPSILON));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::Real;
+use nalgebra::Vector3;
+
+/// How many triangles a leaf holds at most.
+const LEAF_SIZE: usize = 4;
+
+struct Node {
+    min: Vector3<f64>,
+    max: Vector3<f64>,
+    /// Leaves index into the triangles, inner nodes into the nodes.
+    start: usize,
+    count: usize,
+    leaf: bool,
+}
+
+struct Triangle {
+    source: usize,
+    points: [Vector3<f64>; 3],
+}
+
+impl Triangle {
+    fn centroid(&self) -> Vector3<f64> {
+        (self.points[0] + self.points[1] + self.points[2]) / 3.0
+    }
+
+    /// Möller–Trumbore, the distance along the ray to the hit if there is one.
+    fn intersect(&self, origin: &Vector3<f64>, direction: &Vector3<f64>) -> Option<f64> {
+        let [a, b, c] = &self.points;
+        let edge1 = b - a;
+        let edge2 = c - a;
+        let p = direction.cross(&edge2);
+        let determinant = edge1.dot(&p);
+        if determinant.abs() < 1e-12 {
+            return None;
+        }
+        let inverse = 1.0 / determinant;
+        let s = origin - a;
+        let u = s.dot(&p) * inverse;
+        if !(0.0..=1.0).contains(&u) {
+            return None;
+        }
+        let q = s.cross(&edge1);
+        let v = direction.dot(&q) * inverse;
+        if v < 0.0 || u + v > 1.0 {
+            return None;
+        }
+        Some(edge2.dot(&q) * inverse)
+    }
+}
+
+fn bounds<'a>(triangles: impl Iterator<Item = &'a Triangle>) -> (Vector3<f64>, Vector3<f64>) {
+    triangles.flat_map(|triangle| triangle.points.iter()).fold(
+        (
+            Vector3::repeat(f64::INFINITY),
+            Vector3::repeat(f64::NEG_INFINITY),
+        ),
+        |(min, max), point| (min.inf(point), max.sup(point)),
+    )
+}
+
+/// Slab test, whether the ray enters the box before `max_distance`.
+fn hits_box(
+    min: &Vector3<f64>,
+    max: &Vector3<f64>,
+    origin: &Vector3<f64>,
+    inverse: &Vector3<f64>,
+    max_distance: f64,
+) -> bool {
+    let mut near = 0.0f64;
+    let mut far = max_distance;
+    for axis in 0..3 {
+        let t0 = (min[axis] - origin[axis]) * inverse[axis];
+        let t1 = (max[axis] - origin[axis]) * inverse[axis];
+        near = near.max(t0.min(t1));
+        far = far.min(t0.max(t1));
+    }
+    near <= far
+}
+
+/// Bounding volume hierarchy over the triangles of a mesh or scene, for casting shadow rays.
+pub struct Bvh {
+    nodes: Vec<Node>,
+    triangles: Vec<Triangle>,
+}
+
+impl Bvh {
+    /// Builds the hierarchy over polygons, fanning them out into triangles.
+    /// Hits report the index of the polygon they came from.
+    pub fn new<'a, T: Real>(polygons: impl Iterator<Item = &'a [Vector3<T>]>) -> Self {
+        let mut triangles = Vec::new();
+        for (source, points) in polygons.enumerate() {
+            for i in 1..points.len().saturating_sub(1) {
+                triangles.push(Triangle {
+                    source,
+                    points: [points[0], points[i], points[i + 1]]
+                        .map(|point| point.map(Into::into)),
+                });
+            }
+        }
+
+        let mut nodes = Vec::new();
+        let (min, max) = bounds(triangles.iter());
+        nodes.push(Node {
+            min,
+            max,
+            start: 0,
+            count: triangles.len(),
+            leaf: true,
+        });
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let Node {
+                min,
+                max,
+                start,
+                count,
+                ..
+            } = nodes[index];
+            if count <= LEAF_SIZE {
+                continue;
+            }
+            // median split along the longest axis
+            let axis = (max - min).imax();
+            let range = &mut triangles[start..start + count];
+            range.select_nth_unstable_by(count / 2, |a, b| {
+                a.centroid()[axis].total_cmp(&b.centroid()[axis])
+            });
+            let children = nodes.len();
+            for (child_start, child_count) in
+                [(start, count / 2), (start + count / 2, count - count / 2)]
+            {
+                let (min, max) = bounds(triangles[child_start..child_start + child_count].iter());
+                stack.push(nodes.len());
+                nodes.push(Node {
+                    min,
+                    max,
+                    start: child_start,
+                    count: child_count,
+                    leaf: true,
+                });
+            }
+            nodes[index].start = children;
+            nodes[index].leaf = false;
+        }
+        Self { nodes, triangles }
+    }
+
+    /// Length of the diagonal of the box around everything.
+    pub fn extent(&self) -> f64 {
+        self.nodes
+            .first()
+            .filter(|_| !self.triangles.is_empty())
+            .map_or(0.0, |root| (root.max - root.min).norm())
+    }
+
+    /// Whether anything but polygon `ignore` blocks the ray between `min_distance` and `max_distance`.
+    pub fn occluded(
+        &self,
+        origin: &Vector3<f64>,
+        direction: &Vector3<f64>,
+        min_distance: f64,
+        max_distance: f64,
+        ignore: usize,
+    ) -> bool {
+        if self.triangles.is_empty() {
+            return false;
+        }
+        let inverse = direction.map(|component| 1.0 / component);
+        let mut stack = vec![0];
+        while let Some(index) = stack.pop() {
+            let node = &self.nodes[index];
+            if !hits_box(&node.min, &node.max, origin, &inverse, max_distance) {
+                continue;
+            }
+            if !node.leaf {
+                stack.push(node.start);
+                stack.push(node.start + 1);
+                continue;
+            }
+            let hit = self.triangles[node.start..node.start + node.count]
+                .iter()
+                .filter(|triangle| triangle.source != ignore)
+                .filter_map(|triangle| triangle.intersect(origin, direction))
+                .any(|distance| distance > min_distance && distance < max_distance);
+            if hit {
+                return true;
+            }
+        }
+        false
+    }
+}
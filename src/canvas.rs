@@ -0,0 +1,20 @@
+use std::fmt::Write;
+
+/// A Tcl script drawing the polygons in order on the Tk canvas with path name `canvas`,
+/// each a list of pixel coordinates and a fill color. Without an outline color edges aren't drawn.
+pub fn script(
+    canvas: &str,
+    outline: Option<&str>,
+    polygons: impl Iterator<Item = (Vec<(f64, f64)>, String)>,
+) -> String {
+    let outline = outline.unwrap_or("{}");
+    let mut script = String::new();
+    for (points, fill) in polygons {
+        write!(script, "{} create polygon", canvas).unwrap();
+        for (x, y) in points {
+            write!(script, " {:.2} {:.2}", x, y).unwrap();
+        }
+        writeln!(script, " -fill {} -outline {}", fill, outline).unwrap();
+    }
+    script
+}
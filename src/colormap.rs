@@ -0,0 +1,86 @@
+use nalgebra::Vector3;
+
+/// How many entries the built in colormaps get sampled into.
+const LUT_SIZE: usize = 256;
+
+// 6th degree polynomial fits of the matplotlib colormaps, lowest degree first
+const VIRIDIS: [[f64; 3]; 7] = [
+    [0.2777273272234177, 0.005407344544966578, 0.3340998053353061],
+    [0.1050930431085774, 1.404613529898575, 1.384590162594685],
+    [-0.3308618287255563, 0.214847559468213, 0.09509516302823659],
+    [-4.634230498983486, -5.799100973351585, -19.33244095627987],
+    [6.228269936347081, 14.17993336680509, 56.69055260068105],
+    [4.776384997670288, -13.74514537774601, -65.35303263337234],
+    [-5.435455855934631, 4.645852612178535, 26.3124352495832],
+];
+const INFERNO: [[f64; 3]; 7] = [
+    [
+        0.0002189403691192265,
+        0.001651004631001012,
+        -0.01948089843709184,
+    ],
+    [0.1065134194856116, 0.5639564367884091, 3.932712388889277],
+    [11.60249308247187, -3.972853965665698, -15.9423941062914],
+    [-41.70399613139459, 17.43639888205313, 44.35414519872813],
+    [77.162935699427, -33.40235894210092, -81.80730925738993],
+    [-71.31942824499214, 32.62606426397723, 73.20951985803202],
+    [25.13112622477341, -12.24266895238567, -23.07032500287172],
+];
+const MAGMA: [[f64; 3]; 7] = [
+    [
+        -0.002136485053939582,
+        -0.000749655052795221,
+        -0.005386127855323933,
+    ],
+    [0.2516605407371642, 0.6775232436837668, 2.494026599312351],
+    [8.353717279216625, -3.577719514958484, 0.3144679030132573],
+    [-27.66873308576866, 14.26473078096533, -13.64921318813922],
+    [52.17613981234068, -27.94360607168351, 12.94416944238394],
+    [-50.76852536473588, 29.04658282127291, 4.23415299384598],
+    [18.65570506591883, -11.48977351997711, -5.601961508734096],
+];
+
+fn polynomial(coefficients: &[[f64; 3]; 7]) -> Vec<Vector3<f64>> {
+    (0..LUT_SIZE)
+        .map(|i| {
+            let t = i as f64 / (LUT_SIZE - 1) as f64;
+            coefficients
+                .iter()
+                .rev()
+                .fold(Vector3::zeros(), |acc: Vector3<f64>, c| {
+                    acc * t + Vector3::new(c[0], c[1], c[2])
+                })
+                .map(|channel| channel.clamp(0.0, 1.0))
+        })
+        .collect()
+}
+
+/// The lookup table of a built in colormap.
+pub fn named(name: &str) -> Option<Vec<Vector3<f64>>> {
+    match name {
+        "viridis" => Some(polynomial(&VIRIDIS)),
+        "inferno" => Some(polynomial(&INFERNO)),
+        "magma" => Some(polynomial(&MAGMA)),
+        "grayscale" | "gray" | "grey" => Some(vec![Vector3::zeros(), Vector3::repeat(1.0)]),
+        _ => None,
+    }
+}
+
+/// Relative luminance, so colored lights still map onto a colormap sensibly.
+pub fn luminance(color: &Vector3<f64>) -> f64 {
+    0.2126 * color[0] + 0.7152 * color[1] + 0.0722 * color[2]
+}
+
+/// Linearly interpolates a lookup table at `t` between 0 and 1.
+pub fn sample(lut: &[Vector3<f64>], t: f64) -> Vector3<f64> {
+    let position = t.clamp(0.0, 1.0) * (lut.len() - 1) as f64;
+    let index = (position as usize).min(lut.len() - 1);
+    let next = (index + 1).min(lut.len() - 1);
+    lut[index].lerp(&lut[next], position - index as f64)
+}
+
+/// Formats a color with channels between 0 and 1 the way Tk expects it, `#rrggbb`.
+pub fn hex(color: &Vector3<f64>) -> String {
+    let [r, g, b] = [0, 1, 2].map(|i| (color[i].clamp(0.0, 1.0) * 255.0).round() as u8);
+    format!("#{:02x}{:02x}{:02x}", r, g, b)
+}
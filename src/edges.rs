@@ -0,0 +1,110 @@
+use crate::Real;
+use nalgebra::{Vector2, Vector3};
+use std::collections::HashMap;
+
+/// An edge shared by one or more polygons.
+pub struct Edge {
+    pub points: [Vector3<f64>; 2],
+    /// Indices of the polygons on either side, one for boundary edges.
+    pub polygons: Vec<usize>,
+}
+
+/// Polygons sharing a vertex reference the same point in the file, so their coordinates match exactly.
+fn key(point: &Vector3<f64>) -> [u64; 3] {
+    [point[0].to_bits(), point[1].to_bits(), point[2].to_bits()]
+}
+
+/// Every edge of the polygons once, no matter how many polygons share it.
+pub fn unique<'a, T: Real>(polygons: impl Iterator<Item = &'a [Vector3<T>]>) -> Vec<Edge> {
+    let mut edges: Vec<Edge> = Vec::new();
+    let mut indices: HashMap<_, usize> = HashMap::new();
+    for (polygon, points) in polygons.enumerate() {
+        for (i, a) in points.iter().enumerate() {
+            let a: Vector3<f64> = a.map(Into::into);
+            let b: Vector3<f64> = points[(i + 1) % points.len()].map(Into::into);
+            let (a_key, b_key) = (key(&a), key(&b));
+            if a_key == b_key {
+                continue;
+            }
+            let edge_key = if a_key < b_key {
+                (a_key, b_key)
+            } else {
+                (b_key, a_key)
+            };
+            match indices.get(&edge_key) {
+                Some(&index) => edges[index].polygons.push(polygon),
+                None => {
+                    indices.insert(edge_key, edges.len());
+                    edges.push(Edge {
+                        points: [a, b],
+                        polygons: vec![polygon],
+                    });
+                }
+            }
+        }
+    }
+    edges
+}
+
+fn cross(a: &Vector2<f64>, b: &Vector2<f64>) -> f64 {
+    a.x * b.y - a.y * b.x
+}
+
+/// Where the segment from `a` to `b` crosses any of `others`, as fractions of the way along it.
+/// The fractions come out sorted, starting with 0 and ending with 1.
+pub fn crossings(a: &Vector2<f64>, b: &Vector2<f64>, others: &[[Vector2<f64>; 2]]) -> Vec<f64> {
+    let direction = b - a;
+    let mut fractions = vec![0.0, 1.0];
+    for [start, end] in others {
+        let other = end - start;
+        let denominator = cross(&direction, &other);
+        if denominator.abs() < 1e-12 {
+            continue;
+        }
+        let offset = start - a;
+        let s = cross(&offset, &other) / denominator;
+        let t = cross(&offset, &direction) / denominator;
+        if s > 1e-9 && s < 1.0 - 1e-9 && (0.0..=1.0).contains(&t) {
+            fractions.push(s);
+        }
+    }
+    fractions.sort_by(f64::total_cmp);
+    fractions
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shared_edges_come_out_once() {
+        let square = [
+            Vector3::new(0.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+            Vector3::new(0.0, 1.0, 0.0),
+        ];
+        let triangles = [
+            vec![square[0], square[1], square[2]],
+            vec![square[0], square[2], square[3]],
+        ];
+        let edges = unique(triangles.iter().map(|points| &points[..]));
+        assert_eq!(edges.len(), 5);
+        let diagonal = edges.iter().find(|edge| edge.polygons.len() == 2).unwrap();
+        assert_eq!(diagonal.polygons, vec![0, 1]);
+        assert_eq!(diagonal.points, [square[2], square[0]]);
+    }
+
+    #[test]
+    fn crossings_are_sorted_fractions() {
+        let others = [
+            [Vector2::new(0.75, -1.0), Vector2::new(0.75, 1.0)],
+            [Vector2::new(0.25, -1.0), Vector2::new(0.25, 1.0)],
+            // parallel and beyond the end, neither counts
+            [Vector2::new(0.0, 1.0), Vector2::new(1.0, 1.0)],
+            [Vector2::new(2.0, -1.0), Vector2::new(2.0, 1.0)],
+        ];
+        let fractions = crossings(&Vector2::new(0.0, 0.0), &Vector2::new(1.0, 0.0), &others);
+        assert_eq!(fractions, vec![0.0, 0.25, 0.75, 1.0]);
+    }
+}
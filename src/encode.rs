@@ -0,0 +1,107 @@
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+/// Writes 8 bit RGB or RGBA pixels, row by row from the top left, as a PNG.
+pub fn png(path: &Path, width: usize, height: usize, data: &[u8], alpha: bool) -> io::Result<()> {
+    let file = BufWriter::new(File::create(path)?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(if alpha {
+        png::ColorType::Rgba
+    } else {
+        png::ColorType::Rgb
+    });
+    encoder.set_depth(png::BitDepth::Eight);
+    let mut writer = encoder.write_header().map_err(io::Error::other)?;
+    writer.write_image_data(data).map_err(io::Error::other)?;
+    writer.finish().map_err(io::Error::other)
+}
+
+/// 8 bit RGB pixels, row by row from the top left, as a binary PPM.
+pub fn ppm_data(width: usize, height: usize, data: &[u8]) -> Vec<u8> {
+    let mut ppm = format!("P6\n{} {}\n255\n", width, height).into_bytes();
+    ppm.extend_from_slice(data);
+    ppm
+}
+
+pub fn ppm(path: &Path, width: usize, height: usize, data: &[u8]) -> io::Result<()> {
+    std::fs::write(path, ppm_data(width, height, data))
+}
+
+/// 8 bit RGB pixels as the rows of colors a Tk photo image takes, `{#rrggbb #rrggbb ...} {...}`.
+pub fn photo_rows(width: usize, data: &[u8]) -> String {
+    let mut rows = String::with_capacity(data.len() / 3 * 8 + data.len() / width.max(1));
+    for (y, row) in data.chunks(3 * width.max(1)).enumerate() {
+        if y > 0 {
+            rows.push(' ');
+        }
+        rows.push('{');
+        for (x, pixel) in row.chunks_exact(3).enumerate() {
+            if x > 0 {
+                rows.push(' ');
+            }
+            write!(rows, "#{:02x}{:02x}{:02x}", pixel[0], pixel[1], pixel[2]).unwrap();
+        }
+        rows.push('}');
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::texture::Texture;
+    use crate::{Sampling, Wrap};
+    use nalgebra::Vector2;
+
+    const PIXELS: [u8; 12] = [255, 0, 0, 0, 255, 0, 0, 0, 255, 255, 255, 255];
+
+    /// Reads a 2 by 2 image back through the texture loader, texel by texel.
+    fn decode(path: &Path) -> Vec<u8> {
+        let texture = Texture::load(path).unwrap();
+        std::fs::remove_file(path).unwrap();
+        [(0.25, 0.75), (0.75, 0.75), (0.25, 0.25), (0.75, 0.25)]
+            .iter()
+            .flat_map(|(u, v)| {
+                let texel = texture.sample(&Vector2::new(*u, *v), Sampling::Nearest, Wrap::Clamp);
+                [0, 1, 2].map(|i| (texel[i] * 255.0).round() as u8)
+            })
+            .collect()
+    }
+
+    fn temporary(extension: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("tkrender-{}.{}", std::process::id(), extension))
+    }
+
+    #[test]
+    fn ppm_round_trips() {
+        assert!(ppm_data(2, 2, &PIXELS).starts_with(b"P6\n2 2\n255\n"));
+        let path = temporary("ppm");
+        ppm(&path, 2, 2, &PIXELS).unwrap();
+        assert_eq!(decode(&path), PIXELS);
+    }
+
+    #[test]
+    fn png_round_trips() {
+        let path = temporary("png");
+        png(&path, 2, 2, &PIXELS, false).unwrap();
+        assert_eq!(decode(&path), PIXELS);
+
+        // the loader drops alpha, the colors still have to survive
+        let rgba = PIXELS
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 128])
+            .collect::<Vec<u8>>();
+        png(&path, 2, 2, &rgba, true).unwrap();
+        assert_eq!(decode(&path), PIXELS);
+    }
+
+    #[test]
+    fn photo_rows_lists_colors_row_by_row() {
+        assert_eq!(
+            photo_rows(2, &PIXELS),
+            "{#ff0000 #00ff00} {#0000ff #ffffff}"
+        );
+    }
+}
@@ -1,193 +1,3059 @@
+use pyo3::exceptions::{
+    PyFileNotFoundError, PyIOError, PyIndexError, PyRuntimeError, PyTypeError, PyValueError,
+};
 use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict};
 include!(concat!(env!("OUT_DIR"), "/module.rs"));
-use nalgebra::{Matrix3, Rotation3, Vector3};
+use nalgebra::{Matrix3, RealField, Rotation3, SVector, Vector2, Vector3};
 use rayon::prelude::*;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
 
-struct Polygon {
-    points: Vec<Vector3<f64>>,
-    normal: Vector3<f64>,
+mod bsp;
+mod bvh;
+mod canvas;
+mod colormap;
+mod edges;
+mod encode;
+mod lighting;
+mod newell;
+mod plane;
+mod raster;
+mod svg;
+mod terminal;
+mod texture;
+
+/// Which side of the polygons gets discarded before drawing.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Culling {
+    Back,
+    Front,
+    None,
+}
+
+impl Culling {
+    fn parse(name: &str, disable_culling: bool) -> PyResult<Self> {
+        if disable_culling {
+            return Ok(Culling::None);
+        }
+        match name {
+            "back" => Ok(Culling::Back),
+            "front" => Ok(Culling::Front),
+            "none" => Ok(Culling::None),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown culling mode '{}', expected 'back', 'front' or 'none'",
+                name
+            ))),
+        }
+    }
+}
+
+/// Which view space depth of a polygon the painter's algorithm orders by.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    Centroid,
+    Max,
+    Min,
+}
+
+impl SortKey {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "centroid" => Ok(SortKey::Centroid),
+            "max" => Ok(SortKey::Max),
+            "min" => Ok(SortKey::Min),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown sort key '{}', expected 'centroid', 'max' or 'min'",
+                name
+            ))),
+        }
+    }
+
+    fn depth(&self, points: &[Vector3<f64>]) -> f64 {
+        let depths = points.iter().map(|point| point[2]);
+        match self {
+            SortKey::Centroid => depths.sum::<f64>() / points.len() as f64,
+            SortKey::Max => depths.fold(f64::NEG_INFINITY, f64::max),
+            SortKey::Min => depths.fold(f64::INFINITY, f64::min),
+        }
+    }
+}
+
+/// How the polygons handed to the painter's algorithm get ordered.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Visibility {
+    /// Sorts by depth alone, which can paint overlapping polygons in the wrong order.
+    Sort,
+    /// Splits polygons until the Newell–Newell–Sancha tests agree on an order, handing
+    /// whatever it can't untangle to a BSP tree, see `newell::order`.
+    Newell,
+    /// Traverses a BSP tree built from the scene ahead of time.
+    Bsp,
+}
+
+impl Visibility {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "sort" => Ok(Visibility::Sort),
+            "newell" => Ok(Visibility::Newell),
+            "bsp" => Ok(Visibility::Bsp),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown visibility mode '{}', expected 'sort', 'newell' or 'bsp'",
+                name
+            ))),
+        }
+    }
+}
+
+/// What geometry can be stored as. Culling and moving it around happens in that precision,
+/// shading and sorting always in double precision.
+trait Real: RealField + Copy + Into<f64> {}
+
+impl Real for f32 {}
+impl Real for f64 {}
+
+/// Widens a vector to double precision.
+fn double<T: Real, const D: usize>(vector: &SVector<T, D>) -> SVector<f64, D> {
+    vector.map(Into::into)
+}
+
+/// Converts a vector between precisions, going through double precision.
+fn convert<S: Real, T: Real, const D: usize>(vector: &SVector<S, D>) -> SVector<T, D> {
+    vector.map(|value| nalgebra::convert(value.into()))
+}
+
+struct Polygon<T = f64> {
+    points: Vec<Vector3<T>>,
+    normal: Vector3<T>,
+    normals: Vec<Vector3<T>>,
+    /// Texture coordinates of every vertex, empty when the file has none.
+    uvs: Vec<Vector2<T>>,
+    material: usize,
+}
+
+impl<T: Real> Polygon<T> {
+    fn new(points: Vec<Vector3<T>>, normals: Vec<Vector3<T>>) -> Self {
+        let a: Vector3<f64> = double(&points[0]);
+        let b: Vector3<f64> = double(&points[1]);
+        let c: Vector3<f64> = double(&points[2]);
+
+        fn inv_sqrt(number: f64) -> f64 {
+            let mut i: i64 = number.to_bits() as i64;
+            i = 0x5fe6eb50c7b537a9_i64.wrapping_sub(i >> 1);
+            let y = f64::from_bits(i as u64);
+            y * (1.5 - (number * 0.5 * y * y))
+        }
+
+        // calculate surface normal of the polygon
+        // let normal: Vector3<f64> = points.iter().reduce(|a, b| &a.cross(b)).unwrap();
+        let normal: Vector3<f64> = (b - a).cross(&(c - a));
+        let norm = inv_sqrt(normal.norm_squared());
+        Self {
+            points,
+            normal: (normal * norm).cast(),
+            normals,
+            uvs: Vec::new(),
+            material: 0,
+        }
+    }
+    fn transform_inplace(&mut self, matrix: Matrix3<T>) {
+        for point in self.points.iter_mut() {
+            *point = matrix * *point;
+        }
+        for normal in self.normals.iter_mut() {
+            *normal = matrix * *normal;
+        }
+        self.normal = matrix * self.normal;
+    }
+    fn transform(&self, matrix: Matrix3<T>) -> Self {
+        let points = self.points.iter().map(|point| matrix * point).collect();
+        let normals = self.normals.iter().map(|normal| matrix * normal).collect();
+        Self {
+            points,
+            normal: matrix * self.normal,
+            normals,
+            uvs: self.uvs.clone(),
+            material: self.material,
+        }
+    }
+    fn centroid(&self) -> Vector3<f64> {
+        self.points.iter().map(double).sum::<Vector3<f64>>() / self.points.len() as f64
+    }
+    fn faces(&self, eye: &Vector3<T>) -> bool {
+        // the polygon is front facing when its normal points back towards the camera
+        self.normal.dot(&(self.points[0] - eye)) < T::zero()
+    }
+    fn is_culled(&self, eye: &Vector3<T>, culling: Culling) -> bool {
+        match culling {
+            Culling::Back => !self.faces(eye),
+            Culling::Front => self.faces(eye),
+            Culling::None => false,
+        }
+    }
+}
+
+/// Where the lights get evaluated on each polygon.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Shading {
+    Flat,
+    Gouraud,
+    Toon,
+}
+
+impl Shading {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "flat" => Ok(Shading::Flat),
+            "gouraud" => Ok(Shading::Gouraud),
+            "toon" => Ok(Shading::Toon),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown shading '{}', expected 'flat', 'gouraud' or 'toon'",
+                name
+            ))),
+        }
+    }
+}
+
+/// How quickly fog thickens with depth.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum FogMode {
+    Linear,
+    Exponential,
+    Exponential2,
+}
+
+impl FogMode {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "linear" => Ok(FogMode::Linear),
+            "exp" => Ok(FogMode::Exponential),
+            "exp2" => Ok(FogMode::Exponential2),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown fog mode '{}', expected 'linear', 'exp' or 'exp2'",
+                name
+            ))),
+        }
+    }
+}
+
+/// How supersampled images get filtered down to their final size.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Filter {
+    Box,
+    Tent,
+}
+
+impl Filter {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "box" => Ok(Filter::Box),
+            "tent" => Ok(Filter::Tent),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown filter '{}', expected 'box' or 'tent'",
+                name
+            ))),
+        }
+    }
+
+    /// How far from a pixel center in pixels samples still count towards it.
+    fn radius(&self) -> f64 {
+        match self {
+            Filter::Box => 0.5,
+            Filter::Tent => 1.0,
+        }
+    }
+
+    fn weight(&self, distance: f64) -> f64 {
+        match self {
+            Filter::Box => (distance.abs() < 0.5) as u8 as f64,
+            Filter::Tent => (1.0 - distance.abs()).max(0.0),
+        }
+    }
+}
+
+/// How textures get sampled between their texels.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Sampling {
+    Nearest,
+    Bilinear,
+}
+
+impl Sampling {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "nearest" => Ok(Sampling::Nearest),
+            "bilinear" => Ok(Sampling::Bilinear),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown sampling '{}', expected 'nearest' or 'bilinear'",
+                name
+            ))),
+        }
+    }
+}
+
+/// What geometry of a mesh gets stored as, single precision taking about a third less memory.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Precision {
+    Double,
+    Single,
+}
+
+impl Precision {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "double" => Ok(Precision::Double),
+            "single" => Ok(Precision::Single),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown precision '{}', expected 'double' or 'single'",
+                name
+            ))),
+        }
+    }
+}
+
+/// What texture coordinates outside of 0 to 1 map to.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Wrap {
+    Repeat,
+    Clamp,
+}
+
+impl Wrap {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "repeat" => Ok(Wrap::Repeat),
+            "clamp" => Ok(Wrap::Clamp),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown wrap mode '{}', expected 'repeat' or 'clamp'",
+                name
+            ))),
+        }
+    }
+}
+
+/// Size of a rendered image and how it gets antialiased.
+struct ImageOptions {
+    width: usize,
+    height: usize,
+    background: Vector3<f64>,
+    samples: usize,
+    filter: Filter,
+    sampling: Sampling,
+    /// Height over width of a pixel as it gets displayed. With it the view keeps its proportions,
+    /// spanning the camera range across the image and as much of it as fits down,
+    /// without it the range gets stretched over both like on the canvas.
+    aspect: Option<f64>,
+}
+
+impl ImageOptions {
+    fn new(width: usize, height: usize, keywords: &Keywords) -> PyResult<Self> {
+        let background: Vec<f64> = keywords.take("background", vec![0.0, 0.0, 0.0])?;
+        let antialias = keywords.take("antialias", 1)?;
+        if !(1..=4).contains(&antialias) {
+            return Err(PyValueError::new_err("antialias has to be between 1 and 4"));
+        }
+        Ok(Self {
+            width,
+            height,
+            background: Vector3::new(background[0], background[1], background[2]),
+            samples: antialias,
+            filter: Filter::parse(keywords.take("filter", "box")?)?,
+            sampling: Sampling::parse(keywords.take("sampling", "bilinear")?)?,
+            aspect: None,
+        })
+    }
+}
+
+/// How a frame gets drawn with characters in a terminal.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TextMode {
+    Ascii,
+    Blocks,
+}
+
+impl TextMode {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "ascii" => Ok(TextMode::Ascii),
+            "blocks" => Ok(TextMode::Blocks),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown text mode '{}', expected 'ascii' or 'blocks'",
+                name
+            ))),
+        }
+    }
+
+    /// How many rows of pixels every line of text shows.
+    fn rows(&self) -> usize {
+        match self {
+            TextMode::Ascii => 1,
+            TextMode::Blocks => 2,
+        }
+    }
+
+    /// Height over width of a pixel, terminal cells being about twice as tall as they are wide.
+    fn aspect(&self) -> f64 {
+        match self {
+            TextMode::Ascii => 2.0,
+            TextMode::Blocks => 1.0,
+        }
+    }
+
+    /// Draws a frame as text, leaving uncovered cells to the terminal unless a `background` is given.
+    fn draw(&self, frame: &raster::Frame, ramp: &str, background: Option<&Vector3<f64>>) -> String {
+        match self {
+            TextMode::Ascii => terminal::ascii(frame, &ramp.chars().collect::<Vec<char>>()),
+            TextMode::Blocks => terminal::blocks(frame, background),
+        }
+    }
+}
+
+/// Which file format a rendered image gets saved as.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ImageFormat {
+    Png,
+    Ppm,
+}
+
+impl ImageFormat {
+    /// Goes by the extension of the file, which has to be able to store `alpha` if asked for.
+    fn parse(path: &Path, alpha: bool) -> PyResult<Self> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_ascii_lowercase());
+        match extension.as_deref() {
+            Some("png") => Ok(ImageFormat::Png),
+            Some("ppm") if alpha => Err(PyValueError::new_err(
+                "PPM files have no alpha channel, save as .png instead",
+            )),
+            Some("ppm") => Ok(ImageFormat::Ppm),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown image format of {:?}, expected a .png or .ppm file",
+                path
+            ))),
+        }
+    }
+
+    fn save(
+        &self,
+        path: &Path,
+        frame: &raster::Frame,
+        background: &Vector3<f64>,
+        alpha: bool,
+    ) -> PyResult<()> {
+        let (width, height) = (frame.width, frame.height);
+        match self {
+            ImageFormat::Png if alpha => encode::png(path, width, height, &frame.to_rgba8(), true)?,
+            ImageFormat::Png => {
+                encode::png(path, width, height, &frame.to_rgb8(background), false)?
+            }
+            ImageFormat::Ppm => encode::ppm(path, width, height, &frame.to_rgb8(background))?,
+        }
+        Ok(())
+    }
+}
+
+/// What rendered images get handed to a Tk photo image as.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PhotoFormat {
+    Rows,
+    Ppm,
+}
+
+impl PhotoFormat {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "rows" => Ok(PhotoFormat::Rows),
+            "ppm" => Ok(PhotoFormat::Ppm),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown photo format '{}', expected 'rows' or 'ppm'",
+                name
+            ))),
+        }
+    }
+
+    fn convert(&self, py: Python, frame: &raster::Frame, background: &Vector3<f64>) -> PyObject {
+        let data = frame.to_rgb8(background);
+        match self {
+            PhotoFormat::Rows => encode::photo_rows(frame.width, &data).into_py(py),
+            PhotoFormat::Ppm => {
+                PyBytes::new(py, &encode::ppm_data(frame.width, frame.height, &data)).into_py(py)
+            }
+        }
+    }
+}
+
+/// A projected polygon along with its color, ready to be painted.
+type Shaded = (Vec<Vec<f64>>, Vector3<f64>);
+
+/// What the shade of every polygon is handed back to Python as.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Output {
+    Shade,
+    Rgb,
+    Hex,
+}
+
+impl Output {
+    fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "shade" => Ok(Output::Shade),
+            "rgb" => Ok(Output::Rgb),
+            "hex" => Ok(Output::Hex),
+            _ => Err(PyValueError::new_err(format!(
+                "unknown output '{}', expected 'shade', 'rgb' or 'hex'",
+                name
+            ))),
+        }
+    }
+
+    /// Hands the shading back to Python, running the brightness through `colormap` first if given.
+    fn convert(
+        &self,
+        py: Python,
+        polygons: Vec<Shaded>,
+        colormap: Option<&Colormap>,
+    ) -> Vec<(Vec<Vec<f64>>, PyObject)> {
+        polygons
+            .into_iter()
+            .map(|(points, color)| {
+                let shade = colormap::luminance(&color);
+                let color = colormap.map_or(color, |colormap| colormap.sample(shade));
+                let shade = match self {
+                    Output::Shade => shade.into_py(py),
+                    Output::Rgb => (color[0], color[1], color[2]).into_py(py),
+                    Output::Hex => colormap::hex(&color).into_py(py),
+                };
+                (points, shade)
+            })
+            .collect()
+    }
+}
+
+/// What shadow rays get cast against, and which polygon they start from.
+struct Shadows<'a> {
+    bvh: &'a bvh::Bvh,
+    polygon: usize,
+    /// Hits closer than this are the surface the ray started from.
+    bias: f64,
+}
+
+/// The keyword arguments every render method shares, taken out one at a time
+/// so a misspelled or unsupported one can be reported once all are read.
+struct Keywords<'py>(Option<&'py PyDict>);
+
+impl<'py> Keywords<'py> {
+    fn new(keywords: Option<&'py PyDict>) -> PyResult<Self> {
+        Ok(Self(keywords.map(PyDict::copy).transpose()?))
+    }
+
+    /// Takes out the keyword `name`, `default` if it wasn't passed.
+    fn take<T: FromPyObject<'py>>(&self, name: &str, default: T) -> PyResult<T> {
+        let Some((keywords, value)) = self
+            .0
+            .and_then(|keywords| Some((keywords, keywords.get_item(name)?)))
+        else {
+            return Ok(default);
+        };
+        keywords.del_item(name)?;
+        value.extract().map_err(|error| {
+            PyTypeError::new_err(format!("argument '{}': {}", name, error.value(value.py())))
+        })
+    }
+
+    /// Fails on the first keyword nothing took.
+    fn finish(self) -> PyResult<()> {
+        match self.0.and_then(|keywords| keywords.keys().iter().next()) {
+            Some(name) => Err(PyTypeError::new_err(format!(
+                "got an unexpected keyword argument {}",
+                name.repr()?
+            ))),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Settings shared by everything that goes through the shaded render pipeline.
+struct RenderOptions {
+    culling: Culling,
+    sort: SortKey,
+    visibility: Visibility,
+    occlusion: bool,
+    lights: Vec<Light>,
+    shading: Shading,
+    material: Option<Material>,
+    fog: Option<Fog>,
+    toon: Toon,
+    shadows: bool,
+    /// Maps the brightness of every shaded color to a color of its own.
+    colormap: Option<Colormap>,
+}
+
+impl RenderOptions {
+    /// Options for the painter's algorithm, which has to order the polygons itself.
+    fn new(keywords: &Keywords) -> PyResult<Self> {
+        let disable_culling = keywords.take("disable_culling", false)?;
+        Ok(Self {
+            culling: Culling::parse(keywords.take("culling", "back")?, disable_culling)?,
+            sort: SortKey::parse(keywords.take("sort", "centroid")?)?,
+            visibility: Visibility::parse(keywords.take("visibility", "sort")?)?,
+            occlusion: !keywords.take("disable_occlusion", false)?,
+            // the rest is shared with the rasterizer, which finds culling already taken
+            ..Self::raster(keywords)?
+        })
+    }
+
+    /// Options for the rasterizer, whose depth buffer takes care of what's visible.
+    fn raster(keywords: &Keywords) -> PyResult<Self> {
+        Ok(Self {
+            culling: Culling::parse(keywords.take("culling", "back")?, false)?,
+            sort: SortKey::Centroid,
+            visibility: Visibility::Sort,
+            occlusion: true,
+            lights: Self::lights(
+                keywords.take("light", None)?,
+                keywords.take("lights", None)?,
+            ),
+            shading: Shading::parse(keywords.take("shading", "flat")?)?,
+            material: keywords.take("material", None)?,
+            fog: keywords.take("fog", None)?,
+            toon: keywords
+                .take::<Option<Toon>>("toon", None)?
+                .unwrap_or_default(),
+            shadows: keywords.take("shadows", false)?,
+            colormap: keywords.take("colormap", None)?,
+        })
+    }
+
+    /// Both ways of passing lights together, a default light if there are none.
+    fn lights(light: Option<Light>, lights: Option<Vec<Light>>) -> Vec<Light> {
+        let mut lights = lights.unwrap_or_default();
+        lights.extend(light);
+        if lights.is_empty() {
+            lights.push(Light::default());
+        }
+        lights
+    }
+
+    /// Sum of the diffuse and specular light of all lights reaching a surface point.
+    fn light(
+        &self,
+        point: &Vector3<f64>,
+        normal: &Vector3<f64>,
+        eye: &Vector3<f64>,
+        material: &Material,
+        shadows: Option<&Shadows>,
+    ) -> (Vector3<f64>, Vector3<f64>) {
+        let view = (eye - point).normalize();
+        let toon = (self.shading == Shading::Toon).then_some(&self.toon);
+        self.lights
+            .iter()
+            .map(|light| light.illuminate(point, normal, &view, material, toon, shadows))
+            .fold(
+                (Vector3::zeros(), Vector3::zeros()),
+                |(diffuse, specular), (d, s)| (diffuse + d, specular + s),
+            )
+    }
+
+    /// Sum of all lights reaching a surface point, clamped to a displayable color.
+    fn illuminate(
+        &self,
+        point: &Vector3<f64>,
+        normal: &Vector3<f64>,
+        eye: &Vector3<f64>,
+        material: &Material,
+        shadows: Option<&Shadows>,
+    ) -> Vector3<f64> {
+        let (diffuse, specular) = self.light(point, normal, eye, material, shadows);
+        let color = (diffuse + specular).map(|channel| channel.clamp(0.0, 1.0));
+        match &self.fog {
+            Some(fog) => fog.apply(&color, point[2] - eye[2]),
+            None => color,
+        }
+    }
+
+    /// Like `illuminate`, but for the rasterizer which textures surfaces per pixel.
+    /// Returns the color added on top of the texture and the light the texture gets multiplied with,
+    /// the latter is black for materials without a texture.
+    fn illuminate_textured(
+        &self,
+        point: &Vector3<f64>,
+        normal: &Vector3<f64>,
+        eye: &Vector3<f64>,
+        material: &Material,
+        shadows: Option<&Shadows>,
+    ) -> (Vector3<f64>, Vector3<f64>) {
+        if material.texture.is_none() {
+            let color = self.illuminate(point, normal, eye, material, shadows);
+            return (color, Vector3::zeros());
+        }
+        let (diffuse, specular) = self.light(point, normal, eye, material, shadows);
+        let diffuse = diffuse.map(|channel| channel.clamp(0.0, 1.0));
+        let specular = specular.map(|channel| channel.clamp(0.0, 1.0));
+        match &self.fog {
+            Some(fog) => {
+                // fog blends linearly, so both parts fade by the same amount
+                let visibility = fog.visibility(point[2] - eye[2]);
+                (fog.color.lerp(&specular, visibility), diffuse * visibility)
+            }
+            None => (specular, diffuse),
+        }
+    }
+
+    fn shade<T: Real>(
+        &self,
+        polygon: &Polygon<T>,
+        eye: &Vector3<f64>,
+        material: &Material,
+        shadows: Option<&Shadows>,
+    ) -> Vector3<f64> {
+        match self.shading {
+            Shading::Flat | Shading::Toon => {
+                let normal = double(&polygon.normal);
+                self.illuminate(&polygon.centroid(), &normal, eye, material, shadows)
+            }
+            Shading::Gouraud => {
+                // a canvas polygon only has one fill, so the vertex colors get averaged
+                polygon
+                    .points
+                    .iter()
+                    .zip(&polygon.normals)
+                    .map(|(point, normal)| {
+                        self.illuminate(&double(point), &double(normal), eye, material, shadows)
+                    })
+                    .sum::<Vector3<f64>>()
+                    / polygon.points.len() as f64
+            }
+        }
+    }
+
+    /// The color and texture tint at every vertex of a polygon, for the rasterizer to interpolate.
+    fn vertex_colors<T: Real>(
+        &self,
+        polygon: &Polygon<T>,
+        eye: &Vector3<f64>,
+        material: &Material,
+        shadows: Option<&Shadows>,
+    ) -> Vec<(Vector3<f64>, Vector3<f64>)> {
+        match self.shading {
+            Shading::Flat | Shading::Toon => {
+                let centroid = polygon.centroid();
+                let normal = double(&polygon.normal);
+                let color = self.illuminate_textured(&centroid, &normal, eye, material, shadows);
+                vec![color; polygon.points.len()]
+            }
+            Shading::Gouraud => polygon
+                .points
+                .iter()
+                .zip(&polygon.normals)
+                .map(|(point, normal)| {
+                    self.illuminate_textured(
+                        &double(point),
+                        &double(normal),
+                        eye,
+                        material,
+                        shadows,
+                    )
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Culls, shades and orders world space polygons back to front for the painter's algorithm.
+/// Shades every polygon that isn't culled, `None` for the culled ones,
+/// along with the toon outline polygons if there are any.
+fn shade_all<T: Real, S: Send>(
+    polygons: &[Polygon<T>],
+    materials: &[Material],
+    camera: &Camera,
+    options: &RenderOptions,
+    shade: impl Fn(&Polygon<T>, &Material, Option<&Shadows>) -> S + Sync,
+) -> (Vec<Option<S>>, Vec<Vec<Vector3<f64>>>) {
+    // culling happens in the precision of the polygons, like in `project_view`
+    let eye = camera.origin.cast::<T>();
+    let bvh = options
+        .shadows
+        .then(|| bvh::Bvh::new(polygons.iter().map(|polygon| &polygon.points[..])));
+    let shades = polygons
+        .par_iter()
+        .enumerate()
+        .map(|(index, polygon)| {
+            if !polygon.is_culled(&eye, options.culling) {
+                let material = options
+                    .material
+                    .as_ref()
+                    .unwrap_or(&materials[polygon.material]);
+                let shadows = bvh.as_ref().map(|bvh| Shadows {
+                    bvh,
+                    polygon: index,
+                    bias: 1e-6 * bvh.extent(),
+                });
+                Some(shade(polygon, material, shadows.as_ref()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    let hull = if options.shading == Shading::Toon && options.toon.outline > 0.0 {
+        polygons
+            .par_iter()
+            .filter(|polygon| !polygon.faces(&eye))
+            .map(|polygon| options.toon.outline(polygon))
+            .collect()
+    } else {
+        Vec::new()
+    };
+    (shades, hull)
+}
+
+fn render<T: Real>(
+    polygons: &[Polygon<T>],
+    materials: &[Material],
+    bsp: Option<&bsp::Bsp>,
+    camera: &Camera,
+    options: &RenderOptions,
+) -> PyResult<Vec<Shaded>> {
+    let (shades, hull) = shade_all(
+        polygons,
+        materials,
+        camera,
+        options,
+        |polygon, material, shadows| options.shade(polygon, &camera.origin, material, shadows),
+    );
+    let visible = || {
+        polygons
+            .par_iter()
+            .zip(shades.par_iter())
+            .filter_map(|(polygon, shade)| {
+                shade.map(|shade| (camera.to_view_all(&polygon.points), shade))
+            })
+            .chain(
+                hull.par_iter()
+                    .map(|points| (camera.to_view_all(points), options.toon.outline_color)),
+            )
+    };
+    let culled = if !options.occlusion {
+        visible().collect()
+    } else {
+        match options.visibility {
+            Visibility::Sort => {
+                let mut keyed = visible()
+                    .map(|(points, shade)| (options.sort.depth(&points), points, shade))
+                    .collect::<Vec<(f64, Vec<Vector3<f64>>, Vector3<f64>)>>();
+                // back to front, so the farthest polygons get painted over
+                keyed.par_sort_by(|(a, _, _), (b, _, _)| b.total_cmp(a));
+                keyed
+                    .into_iter()
+                    .map(|(_, points, shade)| (points, shade))
+                    .collect()
+            }
+            Visibility::Newell => {
+                let (polygons, shades): (Vec<Vec<Vector3<f64>>>, Vec<Vector3<f64>>) =
+                    visible().unzip();
+                newell::order(polygons)
+                    .into_iter()
+                    .map(|(source, points)| (points, shades[source]))
+                    .collect()
+            }
+            Visibility::Bsp => {
+                let bsp = bsp.ok_or_else(|| {
+                    PyValueError::new_err("no BSP tree built, call build_bsp() first")
+                })?;
+                if !hull.is_empty() {
+                    return Err(PyValueError::new_err(
+                        "toon outlines aren't part of the BSP tree, use visibility 'sort' or 'newell'",
+                    ));
+                }
+                bsp.order(&camera.origin)
+                    .into_iter()
+                    .filter_map(|(source, points)| {
+                        shades[*source].map(|shade| (camera.to_view_all(points), shade))
+                    })
+                    .collect::<Vec<(Vec<Vector3<f64>>, Vector3<f64>)>>()
+            }
+        }
+    };
+
+    Ok(culled
+        .into_par_iter()
+        .map(|(points, shade)| {
+            let points = points
+                .into_iter()
+                .map(|point| {
+                    let projected = camera.project(&point);
+                    vec![projected[0], projected[1], point[2] + camera.origin[2]]
+                })
+                .collect();
+            (points, shade)
+        })
+        .collect())
+}
+
+/// Renders polygons into an image with a depth buffer instead of painting them in order.
+fn rasterize<T: Real>(
+    polygons: &[Polygon<T>],
+    materials: &[Material],
+    camera: &Camera,
+    options: &RenderOptions,
+    image: &ImageOptions,
+) -> raster::Frame {
+    // supersampling renders at a multiple of the size and filters it back down
+    let width = image.width * image.samples;
+    let height = image.height * image.samples;
+    let squash = image
+        .aspect
+        .map_or(1.0, |aspect| width as f64 / (height as f64 * aspect));
+    let (colors, hull) = shade_all(
+        polygons,
+        materials,
+        camera,
+        options,
+        |polygon, material, shadows| {
+            let colors = options.vertex_colors(polygon, &camera.origin, material, shadows);
+            let texture = material
+                .texture
+                .as_ref()
+                .map(|texture| (texture.clone(), material.wrap));
+            (colors, texture)
+        },
+    );
+    let outlines = hull.iter().map(|points| {
+        let view = points
+            .iter()
+            .map(|point| raster::Vertex {
+                position: camera.to_view(point),
+                color: options.toon.outline_color,
+                tint: Vector3::zeros(),
+                uv: Vector2::zeros(),
+            })
+            .collect();
+        (view, None)
+    });
+    let visible = polygons
+        .iter()
+        .zip(colors)
+        .filter_map(|(polygon, shade)| {
+            shade.map(|(colors, texture)| {
+                let view = polygon
+                    .points
+                    .iter()
+                    .zip(colors)
+                    .enumerate()
+                    .map(|(i, (point, (color, tint)))| raster::Vertex {
+                        position: camera.to_view(point),
+                        color,
+                        tint,
+                        uv: polygon.uvs.get(i).map_or_else(Vector2::zeros, double),
+                    })
+                    .collect::<Vec<raster::Vertex>>();
+                (view, texture)
+            })
+        })
+        .chain(outlines)
+        .collect::<Vec<_>>();
+
+    let mut triangles = Vec::new();
+    for (view, texture) in &visible {
+        let vertices = raster::clip_near(view)
+            .into_iter()
+            .map(|vertex| {
+                let mut projected = camera.project(&vertex.position);
+                projected[1] *= squash;
+                let (x, y) = camera.to_screen(&projected, width, height);
+                raster::Vertex {
+                    position: Vector3::new(x, y, vertex.position[2]),
+                    ..vertex
+                }
+            })
+            .collect::<Vec<raster::Vertex>>();
+        for i in 1..vertices.len().saturating_sub(1) {
+            triangles.push(raster::Triangle {
+                vertices: [vertices[0], vertices[i], vertices[i + 1]],
+                texture: texture.as_ref().map(|(texture, wrap)| (&**texture, *wrap)),
+            });
+        }
+    }
+    let mut frame = raster::rasterize(&triangles, width, height, image.sampling);
+    if let Some(colormap) = &options.colormap {
+        // mapped per pixel, so interpolated and textured colors follow the colormap too
+        frame
+            .pixels
+            .par_iter_mut()
+            .filter(|pixel| pixel[3] > 0.0)
+            .for_each(|pixel| {
+                *pixel = colormap.sample(colormap::luminance(&pixel.xyz())).push(1.0)
+            });
+    }
+    if image.samples == 1 && image.filter == Filter::Box {
+        return frame;
+    }
+    frame.downsample(image.samples, image.filter.radius(), |distance| {
+        image.filter.weight(distance)
+    })
+}
+
+/// Maps rendered polygons to pixels of an image or canvas with their fill colors as `#rrggbb`,
+/// running the brightness through `colormap` first if given.
+fn to_pixels<'a>(
+    shaded: Vec<Shaded>,
+    camera: &'a Camera,
+    width: usize,
+    height: usize,
+    colormap: Option<&'a Colormap>,
+) -> impl Iterator<Item = (Vec<(f64, f64)>, String)> + 'a {
+    shaded.into_iter().map(move |(points, color)| {
+        let points = points
+            .iter()
+            .map(|point| camera.to_screen(&Vector3::new(point[0], point[1], 0.0), width, height))
+            .collect();
+        let color = match colormap {
+            Some(colormap) => colormap.sample(colormap::luminance(&color)),
+            None => color,
+        };
+        (points, colormap::hex(&color))
+    })
+}
+
+fn hex(color: Vec<f64>) -> String {
+    colormap::hex(&Vector3::new(color[0], color[1], color[2]))
+}
+
+/// Paints rendered polygons into an SVG document sized like an image, see `svg::document`.
+fn to_svg(
+    shaded: Vec<Shaded>,
+    camera: &Camera,
+    width: usize,
+    height: usize,
+    background: Option<Vec<f64>>,
+    stroke: Option<(Vec<f64>, f64)>,
+    colormap: Option<&Colormap>,
+) -> String {
+    let background = background.map(hex);
+    let stroke = stroke.map(|(color, width)| (hex(color), width));
+    let polygons = to_pixels(shaded, camera, width, height, colormap);
+    svg::document(
+        width,
+        height,
+        background.as_deref(),
+        stroke
+            .as_ref()
+            .map(|(color, width)| (color.as_str(), *width)),
+        polygons,
+    )
+}
+
+/// Hands rendered polygons back as flat canvas coordinates and fill colors,
+/// or as a Tcl script drawing them all if the path name of a `canvas` is given.
+#[allow(clippy::too_many_arguments)]
+fn to_canvas(
+    py: Python,
+    shaded: Vec<Shaded>,
+    camera: &Camera,
+    width: usize,
+    height: usize,
+    canvas: Option<&str>,
+    outline: Option<Vec<f64>>,
+    colormap: Option<&Colormap>,
+) -> PyObject {
+    let polygons = to_pixels(shaded, camera, width, height, colormap);
+    let outline = outline.map(hex);
+    match canvas {
+        Some(canvas) => canvas::script(canvas, outline.as_deref(), polygons).into_py(py),
+        None => polygons
+            .map(|(points, fill)| {
+                let coordinates = points
+                    .into_iter()
+                    .flat_map(|(x, y)| [x, y])
+                    .collect::<Vec<f64>>();
+                (coordinates, fill)
+            })
+            .collect::<Vec<(Vec<f64>, String)>>()
+            .into_py(py),
+    }
+}
+
+/// Which edges of a mesh to keep, all unique edges if none of the filters are set.
+struct EdgeFilter {
+    culling: Culling,
+    boundary: bool,
+    /// Smallest angle between the normals of two polygons that makes their edge a crease.
+    crease: Option<f64>,
+    silhouette: bool,
+}
+
+impl EdgeFilter {
+    fn keeps<T: Real>(
+        &self,
+        edge: &edges::Edge,
+        polygons: &[Polygon<T>],
+        eye: &Vector3<T>,
+    ) -> bool {
+        if edge
+            .polygons
+            .iter()
+            .all(|&index| polygons[index].is_culled(eye, self.culling))
+        {
+            return false;
+        }
+        if !self.boundary && self.crease.is_none() && !self.silhouette {
+            return true;
+        }
+        let adjacent = match edge.polygons[..] {
+            [a, b] => Some((&polygons[a], &polygons[b])),
+            _ => None,
+        };
+        let boundary = edge.polygons.len() == 1;
+        let crease = self.crease.is_some_and(|angle| {
+            adjacent.is_some_and(|(a, b)| {
+                let cosine: f64 = a.normal.dot(&b.normal).into();
+                cosine.clamp(-1.0, 1.0).acos() > angle
+            })
+        });
+        let silhouette = adjacent.is_some_and(|(a, b)| a.faces(eye) != b.faces(eye));
+        (self.boundary && boundary) || crease || (self.silhouette && silhouette)
+    }
+}
+
+/// Clips a view space segment to the near plane, nothing being left when it lies behind the camera.
+fn clip_segment(a: Vector3<f64>, b: Vector3<f64>) -> Option<[Vector3<f64>; 2]> {
+    if a.z < raster::NEAR && b.z < raster::NEAR {
+        return None;
+    }
+    let clip = |near: Vector3<f64>, far: Vector3<f64>| {
+        if near.z >= raster::NEAR {
+            near
+        } else {
+            near.lerp(&far, (raster::NEAR - near.z) / (far.z - near.z))
+        }
+    };
+    Some([clip(a, b), clip(b, a)])
+}
+
+/// The view space point on the segment from `a` to `b` that projects `fraction` of the way
+/// between their projections, undoing the perspective divide.
+fn unproject_along(a: &Vector3<f64>, b: &Vector3<f64>, fraction: f64) -> Vector3<f64> {
+    let inverse = (1.0 - fraction) / a.z + fraction / b.z;
+    a.lerp(b, fraction / b.z / inverse)
+}
+
+/// What hidden line removal tests edges against: the polygons that aren't culled,
+/// and the projected contour edges where one of them can start or stop hiding another.
+struct Occluders {
+    bvh: bvh::Bvh,
+    contours: Vec<[Vector2<f64>; 2]>,
+}
+
+impl Occluders {
+    fn new<T: Real>(
+        polygons: &[Polygon<T>],
+        edges: &[edges::Edge],
+        camera: &Camera,
+        culling: Culling,
+    ) -> Self {
+        let eye = &camera.origin.cast::<T>();
+        // culled polygons stay in as empty slices so hits still report the right index
+        let bvh = bvh::Bvh::new(polygons.iter().map(|polygon| {
+            if polygon.is_culled(eye, culling) {
+                &[][..]
+            } else {
+                &polygon.points[..]
+            }
+        }));
+        // between two polygons facing the same way, what's behind one is behind the other too
+        let contours = edges
+            .iter()
+            .filter(|edge| {
+                edge.polygons
+                    .iter()
+                    .any(|&index| !polygons[index].is_culled(eye, culling))
+            })
+            .filter(|edge| match edge.polygons[..] {
+                [a, b] => polygons[a].faces(eye) != polygons[b].faces(eye),
+                _ => true,
+            })
+            .filter_map(|edge| {
+                let [a, b] = edge.points.map(|point| camera.to_view(&point));
+                clip_segment(a, b)
+            })
+            .map(|points| points.map(|point| camera.project(&point).xy()))
+            .collect();
+        Self { bvh, contours }
+    }
+
+    /// The pieces of a view space segment along `edge` that nothing covers, merged where they meet.
+    fn visible(
+        &self,
+        edge: &edges::Edge,
+        camera: &Camera,
+        a: Vector3<f64>,
+        b: Vector3<f64>,
+    ) -> Vec<[Vector3<f64>; 2]> {
+        let fractions = edges::crossings(
+            &camera.project(&a).xy(),
+            &camera.project(&b).xy(),
+            &self.contours,
+        );
+        let mut pieces: Vec<(f64, f64)> = Vec::new();
+        for pair in fractions.windows(2) {
+            let (start, end) = (pair[0], pair[1]);
+            if end - start < 1e-9 {
+                continue;
+            }
+            // visibility only changes at crossings, so the middle speaks for the whole piece
+            let middle = unproject_along(&a, &b, (start + end) / 2.0);
+            let distance = middle.norm();
+            // the polygons along the edge get hit right at the middle, just short of it is in front
+            if self.bvh.occluded(
+                &camera.origin,
+                &(middle / distance),
+                0.0,
+                distance * (1.0 - 1e-6),
+                edge.polygons[0],
+            ) {
+                continue;
+            }
+            match pieces.last_mut() {
+                Some(last) if last.1 == start => last.1 = end,
+                _ => pieces.push((start, end)),
+            }
+        }
+        pieces
+            .into_iter()
+            .map(|(start, end)| [unproject_along(&a, &b, start), unproject_along(&a, &b, end)])
+            .collect()
+    }
+}
+
+/// The edges of polygons that pass the filter, clipped to the near plane and projected.
+/// Points come out like those of `render`, projected x and y with the depth in z.
+/// With `remove_hidden` only the parts of edges that no polygon covers are left,
+/// split where they disappear behind something.
+fn project_edges<T: Real>(
+    polygons: &[Polygon<T>],
+    camera: &Camera,
+    filter: &EdgeFilter,
+    remove_hidden: bool,
+) -> Vec<Vec<Vec<f64>>> {
+    let edges = edges::unique(polygons.iter().map(|polygon| &polygon.points[..]));
+    let occluders = remove_hidden.then(|| Occluders::new(polygons, &edges, camera, filter.culling));
+    let eye = camera.origin.cast::<T>();
+    edges
+        .par_iter()
+        .filter(|edge| filter.keeps(edge, polygons, &eye))
+        .filter_map(|edge| {
+            let [a, b] = edge.points.map(|point| camera.to_view(&point));
+            clip_segment(a, b).map(|[a, b]| (edge, a, b))
+        })
+        .flat_map_iter(|(edge, a, b)| match &occluders {
+            Some(occluders) => occluders.visible(edge, camera, a, b),
+            None => vec![[a, b]],
+        })
+        .map(|segment| {
+            segment
+                .iter()
+                .map(|point| {
+                    let projected = camera.project(point);
+                    vec![projected[0], projected[1], point[2] + camera.origin[2]]
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Placement of a mesh inside a scene, scaled first, then rotated and moved.
+struct Transform {
+    position: Vector3<f64>,
+    rotation: Rotation3<f64>,
+    scale: f64,
+}
+
+impl Transform {
+    fn new(position: Vec<f64>, rotation: Vec<f64>, scale: f64) -> Self {
+        Self {
+            position: Vector3::new(position[0], position[1], position[2]),
+            rotation: Rotation3::from_euler_angles(rotation[0], rotation[1], rotation[2]),
+            scale,
+        }
+    }
+
+    /// Moves a polygon into place in the precision `T`, which may be wider than the one it's stored in.
+    fn apply<S: Real, T: Real>(&self, polygon: &Polygon<S>) -> Polygon<T> {
+        let rotation = self.rotation.matrix().cast::<T>();
+        let position = self.position.cast::<T>();
+        let scale = nalgebra::convert::<f64, T>(self.scale);
+        let points = polygon
+            .points
+            .iter()
+            .map(|point| rotation * (convert(point) * scale) + position)
+            .collect();
+        let normals = polygon
+            .normals
+            .iter()
+            .map(|normal| rotation * convert(normal))
+            .collect();
+        Polygon {
+            points,
+            normal: rotation * convert(&polygon.normal),
+            normals,
+            uvs: polygon.uvs.iter().map(convert).collect(),
+            material: polygon.material,
+        }
+    }
+}
+
+#[derive(Clone)]
+#[pyclass]
+struct Light {
+    source: lighting::Source,
+    color: Vector3<f64>,
+    intensity: f64,
+}
+
+impl Light {
+    /// Blinn-Phong reflection off a surface point with the given world space normal,
+    /// `view` being the unit vector from the point towards the camera.
+    /// With `toon` the diffuse term gets quantized into bands and highlights become solid,
+    /// with `shadows` the light only counts when nothing blocks the way to it.
+    /// The diffuse and specular parts are returned separately, so textures only tint the former.
+    fn illuminate(
+        &self,
+        point: &Vector3<f64>,
+        normal: &Vector3<f64>,
+        view: &Vector3<f64>,
+        material: &Material,
+        toon: Option<&Toon>,
+        shadows: Option<&Shadows>,
+    ) -> (Vector3<f64>, Vector3<f64>) {
+        let radiance = self.color * self.intensity;
+        let Some((towards, strength, distance)) = self.source.incident(point) else {
+            return (radiance.component_mul(&material.diffuse), Vector3::zeros());
+        };
+        let lambert = normal.dot(&towards);
+        if lambert <= 0.0 {
+            return (Vector3::zeros(), Vector3::zeros());
+        }
+        if let Some(shadows) = shadows {
+            if shadows
+                .bvh
+                .occluded(point, &towards, shadows.bias, distance, shadows.polygon)
+            {
+                return (Vector3::zeros(), Vector3::zeros());
+            }
+        }
+        let halfway = (towards + view).normalize();
+        let specular = normal.dot(&halfway).max(0.0).powf(material.shininess);
+        let (diffuse, specular) = match toon {
+            Some(toon) => (
+                toon.quantize(lambert * strength),
+                if specular >= 0.5 { strength } else { 0.0 },
+            ),
+            None => (lambert * strength, specular * strength),
+        };
+        (
+            (material.diffuse * diffuse).component_mul(&radiance),
+            (material.specular * specular).component_mul(&radiance),
+        )
+    }
+
+    fn with_source(source: lighting::Source, color: Vec<f64>, intensity: f64) -> Self {
+        Self {
+            source,
+            color: Vector3::new(color[0], color[1], color[2]),
+            intensity,
+        }
+    }
+}
+
+impl Default for Light {
+    fn default() -> Self {
+        Self::new(vec![0.0, 0.0, 1.0], vec![1.0, 1.0, 1.0], 1.0)
+    }
+}
+
+#[pymethods]
+impl Light {
+    #[new]
+    #[args(
+        direction = "vec![0.0, 0.0, 1.0]",
+        color = "vec![1.0, 1.0, 1.0]",
+        intensity = "1.0"
+    )]
+    fn new(direction: Vec<f64>, color: Vec<f64>, intensity: f64) -> Self {
+        let direction = Vector3::new(direction[0], direction[1], direction[2]).normalize();
+        Self::with_source(
+            lighting::Source::Directional { direction },
+            color,
+            intensity,
+        )
+    }
+
+    #[staticmethod]
+    #[args(color = "vec![1.0, 1.0, 1.0]", intensity = "1.0")]
+    fn ambient(color: Vec<f64>, intensity: f64) -> Self {
+        Self::with_source(lighting::Source::Ambient, color, intensity)
+    }
+
+    #[staticmethod]
+    #[args(
+        direction = "vec![0.0, 0.0, 1.0]",
+        color = "vec![1.0, 1.0, 1.0]",
+        intensity = "1.0"
+    )]
+    fn directional(direction: Vec<f64>, color: Vec<f64>, intensity: f64) -> Self {
+        Self::new(direction, color, intensity)
+    }
+
+    #[staticmethod]
+    #[args(
+        color = "vec![1.0, 1.0, 1.0]",
+        intensity = "1.0",
+        attenuation = "vec![1.0, 0.0, 1.0]"
+    )]
+    fn point(position: Vec<f64>, color: Vec<f64>, intensity: f64, attenuation: Vec<f64>) -> Self {
+        let source = lighting::Source::Point {
+            position: Vector3::new(position[0], position[1], position[2]),
+            attenuation: Vector3::new(attenuation[0], attenuation[1], attenuation[2]),
+        };
+        Self::with_source(source, color, intensity)
+    }
+
+    #[staticmethod]
+    #[args(
+        inner_angle = "0.3",
+        outer_angle = "0.5",
+        color = "vec![1.0, 1.0, 1.0]",
+        intensity = "1.0",
+        attenuation = "vec![1.0, 0.0, 1.0]"
+    )]
+    fn spot(
+        position: Vec<f64>,
+        direction: Vec<f64>,
+        inner_angle: f64,
+        outer_angle: f64,
+        color: Vec<f64>,
+        intensity: f64,
+        attenuation: Vec<f64>,
+    ) -> Self {
+        let source = lighting::Source::Spot {
+            position: Vector3::new(position[0], position[1], position[2]),
+            direction: Vector3::new(direction[0], direction[1], direction[2]).normalize(),
+            cos_inner: inner_angle.cos(),
+            cos_outer: outer_angle.cos(),
+            attenuation: Vector3::new(attenuation[0], attenuation[1], attenuation[2]),
+        };
+        Self::with_source(source, color, intensity)
+    }
+}
+
+#[derive(Clone)]
+#[pyclass]
+struct Material {
+    diffuse: Vector3<f64>,
+    specular: Vector3<f64>,
+    shininess: f64,
+    /// Multiplies the diffuse color, only the rasterizer maps it onto polygons.
+    texture: Option<Arc<texture::Texture>>,
+    wrap: Wrap,
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self {
+            diffuse: Vector3::repeat(1.0),
+            specular: Vector3::zeros(),
+            shininess: 32.0,
+            texture: None,
+            wrap: Wrap::Repeat,
+        }
+    }
+}
+
+/// Reads the materials of an MTL file by name. Textures are looked up relative to the file
+/// and loaded once, even when several materials share them.
+/// Turns an error reading `path` into an exception that names the file.
+fn file_error(path: &Path, error: std::io::Error) -> PyErr {
+    let message = format!("couldn't read {:?}: {}", path, error);
+    match error.kind() {
+        std::io::ErrorKind::NotFound => PyFileNotFoundError::new_err(message),
+        std::io::ErrorKind::InvalidData => PyValueError::new_err(message),
+        _ => PyIOError::new_err(message),
+    }
+}
+
+fn load_materials(path: &Path) -> PyResult<Vec<(String, Material)>> {
+    let contents = std::fs::read_to_string(path).map_err(|error| file_error(path, error))?;
+    let directory = path.parent().unwrap_or_else(|| Path::new(""));
+    let mut textures: HashMap<String, Arc<texture::Texture>> = HashMap::new();
+    let mut materials: Vec<(String, Material)> = Vec::new();
+    let color = |items: &[&str]| -> PyResult<Vector3<f64>> {
+        let channel = |i: usize| {
+            items
+                .get(i)
+                .or(items.first())
+                .and_then(|item| item.parse::<f64>().ok())
+                .ok_or_else(|| PyValueError::new_err(format!("malformed color in {:?}", path)))
+        };
+        Ok(Vector3::new(channel(0)?, channel(1)?, channel(2)?))
+    };
+    for line in contents.lines() {
+        let mut line_iter = line.split_whitespace();
+        let keyword = line_iter.next();
+        let items = line_iter.collect::<Vec<&str>>();
+        if keyword == Some("newmtl") {
+            materials.push((items.join(" "), Material::default()));
+            continue;
+        }
+        let Some((_, material)) = materials.last_mut() else {
+            continue;
+        };
+        match keyword {
+            Some("Kd") => material.diffuse = color(&items)?,
+            Some("Ks") => material.specular = color(&items)?,
+            Some("Ns") => {
+                material.shininess = items
+                    .first()
+                    .and_then(|item| item.parse::<f64>().ok())
+                    .unwrap_or(material.shininess)
+            }
+            Some("map_Kd") => {
+                // options come before the file name, only clamping matters here
+                let Some(name) = items.last() else {
+                    continue;
+                };
+                if let Some(index) = items.iter().position(|item| *item == "-clamp") {
+                    if items.get(index + 1) == Some(&"on") {
+                        material.wrap = Wrap::Clamp;
+                    }
+                }
+                let texture = match textures.get(*name) {
+                    Some(texture) => texture.clone(),
+                    None => {
+                        let path = directory.join(name);
+                        // a missing texture leaves the material untextured, like a missing library
+                        if !path.is_file() {
+                            continue;
+                        }
+                        let texture = texture::Texture::load(&path)
+                            .map_err(|error| file_error(&path, error))?;
+                        let texture = Arc::new(texture);
+                        textures.insert(name.to_string(), texture.clone());
+                        texture
+                    }
+                };
+                material.texture = Some(texture);
+            }
+            _ => {}
+        }
+    }
+    Ok(materials)
+}
+
+#[pymethods]
+impl Material {
+    #[new]
+    #[args(
+        diffuse = "vec![1.0, 1.0, 1.0]",
+        specular = "vec![0.0, 0.0, 0.0]",
+        shininess = "32.0",
+        texture = "None",
+        wrap = "\"repeat\""
+    )]
+    fn new(
+        diffuse: Vec<f64>,
+        specular: Vec<f64>,
+        shininess: f64,
+        texture: Option<String>,
+        wrap: &str,
+    ) -> PyResult<Self> {
+        let texture = match texture {
+            Some(path) => {
+                let path = Path::new(&path);
+                let texture =
+                    texture::Texture::load(path).map_err(|error| file_error(path, error))?;
+                Some(Arc::new(texture))
+            }
+            None => None,
+        };
+        Ok(Self {
+            diffuse: Vector3::new(diffuse[0], diffuse[1], diffuse[2]),
+            specular: Vector3::new(specular[0], specular[1], specular[2]),
+            shininess,
+            texture,
+            wrap: Wrap::parse(wrap)?,
+        })
+    }
+}
+
+#[derive(Clone)]
+#[pyclass]
+struct Toon {
+    thresholds: Vec<f64>,
+    outline: f64,
+    outline_color: Vector3<f64>,
+}
+
+impl Toon {
+    /// Snaps a light intensity down to its band, bands are evenly spaced from 0 to 1.
+    fn quantize(&self, intensity: f64) -> f64 {
+        let band = self
+            .thresholds
+            .iter()
+            .filter(|threshold| intensity >= **threshold)
+            .count();
+        band as f64 / self.thresholds.len() as f64
+    }
+
+    /// Inverted hull outline, back faces pushed out along their vertex normals.
+    /// Painted behind the mesh they only show around its silhouette.
+    fn outline<T: Real>(&self, polygon: &Polygon<T>) -> Vec<Vector3<f64>> {
+        polygon
+            .points
+            .iter()
+            .zip(&polygon.normals)
+            .map(|(point, normal)| double(point) + double(normal) * self.outline)
+            .collect()
+    }
+}
+
+impl Default for Toon {
+    fn default() -> Self {
+        Self::new(4, None, 0.0, vec![0.0, 0.0, 0.0]).unwrap()
+    }
+}
+
+#[pymethods]
+impl Toon {
+    #[new]
+    #[args(
+        bands = "4",
+        thresholds = "None",
+        outline = "0.0",
+        outline_color = "vec![0.0, 0.0, 0.0]"
+    )]
+    fn new(
+        bands: usize,
+        thresholds: Option<Vec<f64>>,
+        outline: f64,
+        outline_color: Vec<f64>,
+    ) -> PyResult<Self> {
+        let thresholds = match thresholds {
+            Some(thresholds) => thresholds,
+            None if bands >= 2 => (1..bands).map(|i| i as f64 / bands as f64).collect(),
+            None => return Err(PyValueError::new_err("toon shading needs at least 2 bands")),
+        };
+        if thresholds.is_empty() || thresholds.windows(2).any(|pair| pair[0] > pair[1]) {
+            return Err(PyValueError::new_err(
+                "toon thresholds have to be a non empty ascending list",
+            ));
+        }
+        Ok(Self {
+            thresholds,
+            outline,
+            outline_color: Vector3::new(outline_color[0], outline_color[1], outline_color[2]),
+        })
+    }
+}
+
+#[derive(Clone)]
+#[pyclass]
+struct Fog {
+    mode: FogMode,
+    color: Vector3<f64>,
+    start: f64,
+    end: f64,
+    density: f64,
+}
+
+impl Fog {
+    /// Blends `color` towards the fog color, by how much fog lies in front of a point at view space `depth`.
+    fn apply(&self, color: &Vector3<f64>, depth: f64) -> Vector3<f64> {
+        self.color.lerp(color, self.visibility(depth))
+    }
+
+    /// How much of a point at view space `depth` shows through the fog, between 0 and 1.
+    fn visibility(&self, depth: f64) -> f64 {
+        let distance = (depth - self.start).max(0.0);
+        let visibility = match self.mode {
+            FogMode::Linear => {
+                if self.end > self.start {
+                    1.0 - distance / (self.end - self.start)
+                } else {
+                    0.0
+                }
+            }
+            FogMode::Exponential => (-self.density * distance).exp(),
+            FogMode::Exponential2 => (-(self.density * distance).powi(2)).exp(),
+        };
+        visibility.clamp(0.0, 1.0)
+    }
+}
+
+#[pymethods]
+impl Fog {
+    #[new]
+    #[args(
+        mode = "\"linear\"",
+        color = "vec![0.0, 0.0, 0.0]",
+        start = "0.0",
+        end = "100.0",
+        density = "0.05"
+    )]
+    fn new(mode: &str, color: Vec<f64>, start: f64, end: f64, density: f64) -> PyResult<Self> {
+        Ok(Self {
+            mode: FogMode::parse(mode)?,
+            color: Vector3::new(color[0], color[1], color[2]),
+            start,
+            end,
+            density,
+        })
+    }
+}
+
+#[derive(Clone)]
+#[pyclass]
+struct Colormap {
+    lut: Vec<Vector3<f64>>,
+}
+
+impl Colormap {
+    fn sample(&self, shade: f64) -> Vector3<f64> {
+        colormap::sample(&self.lut, shade)
+    }
+}
+
+#[pymethods]
+impl Colormap {
+    #[new]
+    #[args(name = "\"inferno\"")]
+    fn new(name: &str) -> PyResult<Self> {
+        let lut = colormap::named(name).ok_or_else(|| {
+            PyValueError::new_err(format!(
+                "unknown colormap '{}', expected 'inferno', 'viridis', 'magma' or 'grayscale'",
+                name
+            ))
+        })?;
+        Ok(Self { lut })
+    }
+
+    #[staticmethod]
+    fn from_lut(colors: Vec<Vec<f64>>) -> PyResult<Self> {
+        if colors.is_empty() {
+            return Err(PyValueError::new_err("a colormap needs at least one color"));
+        }
+        let lut = colors
+            .into_iter()
+            .enumerate()
+            .map(|(index, color)| match color[..] {
+                [r, g, b] if color.iter().all(|channel| (0.0..=1.0).contains(channel)) => {
+                    Ok(Vector3::new(r, g, b))
+                }
+                _ => Err(PyValueError::new_err(format!(
+                    "color {} of the colormap is {:?}, expected 3 channels between 0 and 1",
+                    index, color
+                ))),
+            })
+            .collect::<PyResult<Vec<Vector3<f64>>>>()?;
+        Ok(Self { lut })
+    }
+
+    fn __call__(&self, shade: f64) -> (f64, f64, f64) {
+        let color = self.sample(shade);
+        (color[0], color[1], color[2])
+    }
+
+    fn hex(&self, shade: f64) -> String {
+        colormap::hex(&self.sample(shade))
+    }
 }
 
-impl Polygon {
-    fn new(points: Vec<Vector3<f64>>) -> Self {
-        let a: Vector3<f64> = points[0];
-        let b: Vector3<f64> = points[1];
-        let c: Vector3<f64> = points[2];
+#[derive(Clone)]
+#[pyclass]
+struct Camera {
+    focal: Vector3<f64>,
+    origin: Vector3<f64>,
+    range: f64,
+}
 
-        fn inv_sqrt(number: f64) -> f64 {
-            let mut i: i64 = number.to_bits() as i64;
-            i = 0x5fe6eb50c7b537a9_i64.wrapping_sub(i >> 1);
-            let y = f64::from_bits(i as u64);
-            y * (1.5 - (number * 0.5 * y * y))
-        }
+impl Camera {
+    /// Moves a world space point into view space, where the camera sits at the origin looking down the z axis.
+    fn to_view<T: Real>(&self, point: &Vector3<T>) -> Vector3<f64> {
+        double(&(point - self.origin.cast::<T>()))
+    }
 
-        // calculate surface normal of the polygon
-        // let normal: Vector3<f64> = points.iter().reduce(|a, b| &a.cross(b)).unwrap();
-        let normal: Vector3<f64> = (b - a).cross(&(c - a));
-        let norm = inv_sqrt(normal.norm_squared());
+    fn to_view_all<T: Real>(&self, points: &[Vector3<T>]) -> Vec<Vector3<f64>> {
+        points.iter().map(|point| self.to_view(point)).collect()
+    }
+
+    /// Perspective projection of a view space point onto the screen.
+    fn project(&self, point: &Vector3<f64>) -> Vector3<f64> {
+        point * (self.focal[2] / point[2]) + self.focal
+    }
+
+    /// Maps a projected point to pixels, `range` units of the screen spanning the whole image.
+    fn to_screen(&self, point: &Vector3<f64>, width: usize, height: usize) -> (f64, f64) {
+        (
+            width as f64 * (point[0] + self.range / 2.0) / self.range,
+            height as f64 * (point[1] + self.range / 2.0) / self.range,
+        )
+    }
+}
+
+#[pymethods]
+impl Camera {
+    #[new]
+    #[args(range = "20.0")]
+    fn new(focal: Vec<f64>, origin: Vec<f64>, range: f64) -> Self {
         Self {
-            points,
-            normal: normal * norm,
+            focal: Vector3::new(focal[0], focal[1], focal[2]),
+            origin: Vector3::new(origin[0], origin[1], origin[2]),
+            range,
         }
     }
-    fn transform_inplace(&mut self, matrix: Matrix3<f64>) {
-        for point in self.points.iter_mut() {
-            matrix.mul_to(&point.clone(), point);
-        }
-        matrix.mul_to(&self.normal.clone(), &mut self.normal);
+
+    #[getter]
+    fn get_focal(&self) -> Vec<f64> {
+        vec![self.focal[0], self.focal[1], self.focal[2]]
+    }
+
+    #[setter]
+    fn set_focal(&mut self, focal: Vec<f64>) {
+        self.focal = Vector3::new(focal[0], focal[1], focal[2]);
+    }
+
+    #[getter]
+    fn get_origin(&self) -> Vec<f64> {
+        vec![self.origin[0], self.origin[1], self.origin[2]]
     }
-    fn transform(&self, matrix: Matrix3<f64>) -> Self {
-        let mut points = self.points.clone();
-        for point in points.iter_mut() {
-            matrix.mul_to(&point.clone(), point);
+
+    #[setter]
+    fn set_origin(&mut self, origin: Vec<f64>) {
+        self.origin = Vector3::new(origin[0], origin[1], origin[2]);
+    }
+
+    #[getter]
+    fn get_range(&self) -> f64 {
+        self.range
+    }
+
+    #[setter]
+    fn set_range(&mut self, range: f64) {
+        self.range = range;
+    }
+}
+
+/// How far loading a mesh has come, shared with the thread doing it.
+#[derive(Default)]
+struct Progress {
+    bytes: AtomicUsize,
+    total: AtomicUsize,
+    faces: AtomicUsize,
+    cancelled: AtomicBool,
+}
+
+impl Progress {
+    fn check(&self) -> PyResult<()> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            Err(PyRuntimeError::new_err("loading the mesh was cancelled"))
+        } else {
+            Ok(())
         }
-        let mut normal = self.normal.clone();
-        matrix.mul_to(&self.normal.clone(), &mut normal);
-        Self { points, normal }
     }
 }
 
+/// The point, uv and normal indices of every vertex of a face.
+type Face = Vec<(usize, Option<usize>, Option<usize>)>;
+
+/// The vertex data of an OBJ file that faces index into.
+struct Vertices<'a> {
+    points: &'a [Vector3<f64>],
+    uvs: &'a [Vector2<f64>],
+    normals: &'a [Vector3<f64>],
+    /// Averaged normals for the vertices that have none in the file.
+    smooth: &'a [Vector3<f64>],
+}
+
+impl Vertices<'_> {
+    /// Builds a polygon in the precision `T` for every face and its material.
+    fn polygons<T: Real>(&self, faces: Vec<(Face, usize)>, progress: &Progress) -> Vec<Polygon<T>> {
+        faces
+            .into_par_iter()
+            .map(|(face, material)| {
+                let vertices = face
+                    .iter()
+                    .map(|(index, _, _)| self.points[*index].cast())
+                    .collect();
+                let vertex_normals = face
+                    .iter()
+                    .map(|(index, _, normal)| {
+                        normal
+                            .map_or(self.smooth[*index], |normal| self.normals[normal])
+                            .cast()
+                    })
+                    .collect();
+                let mut polygon = Polygon::new(vertices, vertex_normals);
+                if face.iter().all(|(_, uv, _)| uv.is_some()) {
+                    polygon.uvs = face
+                        .iter()
+                        .filter_map(|(_, uv, _)| uv.map(|uv| self.uvs[uv].cast()))
+                        .collect();
+                }
+                polygon.material = material;
+                progress.faces.fetch_add(1, Ordering::Relaxed);
+                polygon
+            })
+            .collect()
+    }
+}
+
+/// The polygons of a mesh in the precision they're stored in.
+enum Geometry {
+    Double(Vec<Polygon>),
+    Single(Vec<Polygon<f32>>),
+}
+
+/// Evaluates `$body` with `$polygons` bound to the polygons of a geometry, whatever their precision.
+macro_rules! with_polygons {
+    ($geometry:expr, $polygons:ident => $body:expr) => {
+        match $geometry {
+            Geometry::Double($polygons) => $body,
+            Geometry::Single($polygons) => $body,
+        }
+    };
+}
+
+/// Projects the polygons that aren't culled, in whatever precision they're stored in.
+fn project_view<T: Real>(
+    polygons: &[Polygon<T>],
+    focal: &Vector3<T>,
+    origin: &Vector3<T>,
+    culling: Culling,
+) -> Vec<Vec<Vec<f64>>> {
+    polygons
+        .par_iter()
+        .filter_map(|polygon| {
+            if !polygon.is_culled(origin, culling) {
+                let mut points = Vec::new();
+                for point in &polygon.points {
+                    let mut point = *point;
+                    point -= origin;
+                    point *= focal[2] / point[2];
+                    point += focal;
+                    points.push(vec![point[0].into(), point[1].into(), point[2].into()]);
+                }
+                Some(points)
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
 #[pyclass]
 struct Mesh {
-    polygons: Vec<Polygon>,
+    geometry: Geometry,
+    materials: Vec<Material>,
+    bsp: Option<bsp::Bsp>,
 }
 
-#[pymethods]
 impl Mesh {
-    #[new]
-    fn load(path: String) -> Self {
-        let contents = std::fs::read_to_string(path).unwrap();
+    /// Reads a Wavefront OBJ file along with the materials it references,
+    /// counting bytes and faces in `progress` and giving up once it gets cancelled.
+    fn parse(path: &str, progress: &Progress, precision: Precision) -> PyResult<Self> {
+        let contents =
+            std::fs::read_to_string(path).map_err(|error| file_error(Path::new(path), error))?;
+        progress.total.store(contents.len(), Ordering::Relaxed);
+        let directory = Path::new(path).parent().unwrap_or_else(|| Path::new(""));
         let mut points = Vec::new();
+        let mut uvs = Vec::new();
+        let mut normals = Vec::new();
         let mut faces = Vec::new();
-        for line in contents.lines() {
+        // faces before any usemtl get the default material
+        let mut materials = vec![Material::default()];
+        let mut names: HashMap<String, usize> = HashMap::new();
+        let mut current = 0;
+        for (number, line) in contents.split_inclusive('\n').enumerate() {
+            progress.check()?;
+            progress.bytes.fetch_add(line.len(), Ordering::Relaxed);
             let mut line_iter = line.split_whitespace();
-            match line_iter.next() {
+            let keyword = line_iter.next();
+            let malformed = || {
+                PyValueError::new_err(format!(
+                    "malformed '{}' on line {} of {:?}",
+                    keyword.unwrap_or_default(),
+                    number + 1,
+                    path
+                ))
+            };
+            let float = |item: Option<&&str>| {
+                item.and_then(|item| item.parse::<f64>().ok())
+                    .ok_or_else(malformed)
+            };
+            // indices start at one, empty ones are left out
+            let index = |item: Option<&str>| match item {
+                None | Some("") => Ok(None),
+                Some(item) => item
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|index| index.checked_sub(1))
+                    .map(Some)
+                    .ok_or_else(malformed),
+            };
+            match keyword {
                 Some("v") => {
                     let items = line_iter.collect::<Vec<&str>>();
-                    let x = items[0].parse::<f64>().unwrap();
-                    let y = items[1].parse::<f64>().unwrap();
-                    let z = items[2].parse::<f64>().unwrap();
+                    let x = float(items.first())?;
+                    let y = float(items.get(1))?;
+                    let z = float(items.get(2))?;
                     points.push(Vector3::new(x, y, z));
                 }
+                Some("vt") => {
+                    let items = line_iter.collect::<Vec<&str>>();
+                    let u = float(items.first())?;
+                    let v = match items.get(1) {
+                        Some(v) => float(Some(v))?,
+                        None => 0.0,
+                    };
+                    uvs.push(Vector2::new(u, v));
+                }
+                Some("vn") => {
+                    let items = line_iter.collect::<Vec<&str>>();
+                    let x = float(items.first())?;
+                    let y = float(items.get(1))?;
+                    let z = float(items.get(2))?;
+                    normals.push(Vector3::new(x, y, z).normalize());
+                }
                 Some("f") => {
                     // TODO: Support more than 3 vertices per face
                     let items = line_iter
-                        .map(|item| item.split("/").next().unwrap())
-                        .collect::<Vec<&str>>();
-                    let a = items[0].parse::<usize>().unwrap() - 1;
-                    let b = items[1].parse::<usize>().unwrap() - 1;
-                    let c = items[2].parse::<usize>().unwrap() - 1;
-                    faces.push(vec![a, b, c]);
+                        .take(3)
+                        .map(|item| {
+                            // v, v/vt, v//vn or v/vt/vn
+                            let mut indices = item.split('/');
+                            let point = index(indices.next())?.ok_or_else(malformed)?;
+                            let uv = index(indices.next())?;
+                            let normal = index(indices.next())?;
+                            Ok((point, uv, normal))
+                        })
+                        .collect::<PyResult<Face>>()?;
+                    if items.len() < 3 {
+                        return Err(malformed());
+                    }
+                    // indices refer back to what the file defined before the face
+                    let defined = items.iter().all(|(point, uv, normal)| {
+                        *point < points.len()
+                            && uv.is_none_or(|uv| uv < uvs.len())
+                            && normal.is_none_or(|normal| normal < normals.len())
+                    });
+                    if !defined {
+                        return Err(PyValueError::new_err(format!(
+                            "the face on line {} of {:?} refers to a vertex that isn't defined",
+                            number + 1,
+                            path
+                        )));
+                    }
+                    faces.push((items, current));
+                }
+                Some("mtllib") => {
+                    let library = directory.join(line_iter.collect::<Vec<&str>>().join(" "));
+                    // without its library, every face keeps the default material
+                    if !library.is_file() {
+                        continue;
+                    }
+                    for (name, material) in load_materials(&library)? {
+                        names.insert(name, materials.len());
+                        materials.push(material);
+                    }
+                }
+                Some("usemtl") => {
+                    let name = line_iter.collect::<Vec<&str>>().join(" ");
+                    current = names.get(&name).copied().unwrap_or(0);
                 }
                 _ => {}
             }
         }
-        let polygons = faces
-            .into_par_iter()
-            .map(|face| {
-                let points = face
-                    .into_iter()
-                    .map(|index| points[index].clone())
-                    .collect();
-                Polygon::new(points)
-            })
-            .collect();
-        Self { polygons }
+
+        progress.check()?;
+
+        // vertices without a normal in the file get the area weighted average of their faces
+        let mut smooth = vec![Vector3::zeros(); points.len()];
+        for (face, _) in &faces {
+            let (a, b, c) = (points[face[0].0], points[face[1].0], points[face[2].0]);
+            let normal = (b - a).cross(&(c - a));
+            for (index, _, _) in face {
+                smooth[*index] += normal;
+            }
+        }
+        for normal in smooth.iter_mut() {
+            *normal = normal.try_normalize(0.0).unwrap_or_else(Vector3::zeros);
+        }
+
+        // the polygons get built straight in the precision they're stored in
+        let vertices = Vertices {
+            points: &points,
+            uvs: &uvs,
+            normals: &normals,
+            smooth: &smooth,
+        };
+        let geometry = match precision {
+            Precision::Double => Geometry::Double(vertices.polygons(faces, progress)),
+            Precision::Single => Geometry::Single(vertices.polygons(faces, progress)),
+        };
+        Ok(Self {
+            geometry,
+            materials,
+            bsp: None,
+        })
+    }
+
+    /// Shades and orders the polygons for the painter's algorithm, see `render`.
+    fn paint(&self, py: Python, camera: &Camera, options: &RenderOptions) -> PyResult<Vec<Shaded>> {
+        py.allow_threads(|| {
+            with_polygons!(&self.geometry, polygons => render(
+                polygons,
+                &self.materials,
+                self.bsp.as_ref(),
+                camera,
+                options,
+            ))
+        })
+    }
+
+    /// Rasterizes the polygons into a frame, see `rasterize`.
+    fn frame(
+        &self,
+        py: Python,
+        camera: &Camera,
+        options: &RenderOptions,
+        image: &ImageOptions,
+    ) -> PyResult<raster::Frame> {
+        Ok(py.allow_threads(|| {
+            with_polygons!(&self.geometry, polygons => rasterize(
+                polygons,
+                &self.materials,
+                camera,
+                options,
+                image,
+            ))
+        }))
+    }
+}
+
+#[pymethods]
+impl Mesh {
+    #[new]
+    #[args(precision = "\"double\"")]
+    fn load(py: Python, path: String, precision: &str) -> PyResult<Self> {
+        let precision = Precision::parse(precision)?;
+        // parsing and building polygons is pure Rust, so other Python threads can keep going
+        py.allow_threads(|| Self::parse(&path, &Progress::default(), precision))
     }
 
-    fn rotate_in_place(&mut self, angle_x: f64, angle_y: f64, angle_z: f64) -> PyResult<()> {
+    /// Starts loading a mesh on a background thread, returning right away.
+    #[staticmethod]
+    #[args(progress = "None", precision = "\"double\"")]
+    fn load_async(
+        path: String,
+        progress: Option<PyObject>,
+        precision: &str,
+    ) -> PyResult<MeshLoader> {
+        let precision = Precision::parse(precision)?;
+        let shared = Arc::new(Progress::default());
+        let thread = {
+            let shared = shared.clone();
+            std::thread::spawn(move || Mesh::parse(&path, &shared, precision))
+        };
+        Ok(MeshLoader {
+            progress: shared,
+            thread: Some(thread),
+            callback: progress,
+            reported: None,
+        })
+    }
+
+    #[getter]
+    fn precision(&self) -> &str {
+        match self.geometry {
+            Geometry::Double(_) => "double",
+            Geometry::Single(_) => "single",
+        }
+    }
+
+    #[getter]
+    fn get_material(&self) -> Material {
+        self.materials[0].clone()
+    }
+
+    #[setter]
+    fn set_material(&mut self, material: Material) {
+        self.materials = vec![material];
+        with_polygons!(&mut self.geometry, polygons => {
+            polygons.iter_mut().for_each(|polygon| polygon.material = 0)
+        })
+    }
+
+    fn build_bsp(&mut self, py: Python) -> PyResult<()> {
+        let bsp = py.allow_threads(|| {
+            with_polygons!(&self.geometry, polygons => bsp::Bsp::new(
+                polygons
+                    .iter()
+                    .map(|polygon| polygon.points.iter().map(double).collect())
+                    .enumerate()
+                    .collect(),
+            ))
+        });
+        self.bsp = Some(bsp);
+        Ok(())
+    }
+
+    fn rotate_in_place(
+        &mut self,
+        py: Python,
+        angle_x: f64,
+        angle_y: f64,
+        angle_z: f64,
+    ) -> PyResult<()> {
         let rotation = Rotation3::from_euler_angles(angle_x, angle_y, angle_z);
-        self.polygons
-            .par_iter_mut()
-            .for_each(|polygon| polygon.transform_inplace(rotation.into()));
+        let matrix: Matrix3<f64> = rotation.into();
+        py.allow_threads(|| {
+            with_polygons!(&mut self.geometry, polygons => {
+                let matrix = matrix.cast();
+                polygons
+                    .par_iter_mut()
+                    .for_each(|polygon| polygon.transform_inplace(matrix))
+            });
+            if let Some(bsp) = &mut self.bsp {
+                bsp.transform_inplace(rotation.into());
+            }
+        });
         Ok(())
     }
 
     fn rotate(&self, angle_x: f64, angle_y: f64, angle_z: f64) -> PyResult<Vec<Vec<f64>>> {
         let rotation = Rotation3::from_euler_angles(angle_x, angle_y, angle_z);
-        let polygons = self
-            .polygons
-            .par_iter()
-            .map(|polygon| polygon.transform(rotation.into()))
-            .collect::<Vec<Polygon>>();
+        let matrix: Matrix3<f64> = rotation.into();
+        let points = with_polygons!(&self.geometry, polygons => {
+            let matrix = matrix.cast();
+            polygons
+                .par_iter()
+                .map(|polygon| polygon.transform(matrix).points.iter().map(double).collect())
+                .collect::<Vec<Vec<Vector3<f64>>>>()
+        });
         let mut result = Vec::new();
-        for polygon in polygons {
-            for point in polygon.points {
+        for polygon in points {
+            for point in polygon {
                 result.push(vec![point.x, point.y, point.z]);
             }
         }
         Ok(result)
     }
 
-    #[args(disable_culling = false)]
+    #[args(disable_culling = false, culling = "\"back\"")]
     fn get_view(
         &self,
+        py: Python,
         focal: Vec<f64>,
         origin: Vec<f64>,
         disable_culling: bool,
+        culling: &str,
     ) -> PyResult<Vec<Vec<Vec<f64>>>> {
+        let culling = Culling::parse(culling, disable_culling)?;
         let origin = Vector3::new(origin[0], origin[1], origin[2]);
         // let orientation = Vector3::new(orientation[0], orientation[1], orientation[2]);
         let focal = Vector3::new(focal[0], focal[1], focal[2]);
-        Ok(self
-            .polygons
-            .par_iter()
-            .filter_map(|polygon| {
-                if polygon.normal[2] < 0.0 || disable_culling {
-                    let mut points = Vec::new();
-                    for point in &polygon.points {
-                        let mut point = point.clone();
-                        point -= &origin;
-                        point *= focal[2] / point[2];
-                        point += &focal;
-                        points.push(vec![point[0], point[1], point[2]]);
-                    }
-                    Some(points)
-                } else {
-                    None
-                }
+        Ok(py.allow_threads(|| {
+            with_polygons!(&self.geometry, polygons => {
+                project_view(polygons, &focal.cast(), &origin.cast(), culling)
+            })
+        }))
+    }
+
+    #[args(
+        culling = "\"back\"",
+        boundary = false,
+        crease = "None",
+        silhouette = false,
+        remove_hidden = false
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn get_edges(
+        &self,
+        py: Python,
+        camera: Camera,
+        culling: &str,
+        boundary: bool,
+        crease: Option<f64>,
+        silhouette: bool,
+        remove_hidden: bool,
+    ) -> PyResult<Vec<Vec<Vec<f64>>>> {
+        let filter = EdgeFilter {
+            culling: Culling::parse(culling, false)?,
+            boundary,
+            crease,
+            silhouette,
+        };
+        Ok(py.allow_threads(|| {
+            with_polygons!(&self.geometry, polygons => {
+                project_edges(polygons, &camera, &filter, remove_hidden)
             })
-            .collect::<Vec<Vec<Vec<f64>>>>())
+        }))
     }
 
-    #[args(disable_culling = false, disable_occlusion = false)]
+    #[args(output = "\"shade\"", keywords = "**")]
     fn get_shaded(
         &self,
+        py: Python,
         focal: Vec<f64>,
         origin: Vec<f64>,
-        disable_culling: bool,
-        disable_occlusion: bool,
-    ) -> PyResult<Vec<(Vec<Vec<f64>>, f64)>> {
-        let origin = Vector3::new(origin[0], origin[1], origin[2]);
-        let focal = Vector3::new(focal[0], focal[1], focal[2]);
-        let mut culled = self
-            .polygons
-            .par_iter()
-            .filter_map(|polygon| {
-                if polygon.normal[2] < 0.0 || disable_culling {
-                    let mut points = Vec::new();
-                    for point in &polygon.points {
-                        let mut point_view = point.clone();
-                        point_view -= &origin;
-                        point_view *= focal[2] / point_view[2];
-                        point_view += &focal;
-                        points.push(vec![point_view[0], point_view[1], point[2]]);
-                    }
-                    Some((
-                        points,
-                        polygon.normal.dot(&Vector3::new(0.0, 0.0, 1.0)).abs(),
-                    ))
-                } else {
-                    None
-                }
+        output: &str,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<Vec<(Vec<Vec<f64>>, PyObject)>> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::new(&keywords)?;
+        keywords.finish()?;
+        let output = Output::parse(output)?;
+        let camera = Camera::new(focal, origin, 20.0);
+        let shaded = self.paint(py, &camera, &options)?;
+        Ok(output.convert(py, shaded, options.colormap.as_ref()))
+    }
+
+    #[args(keywords = "**")]
+    fn render_image(
+        &self,
+        py: Python,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<PyObject> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::raster(&keywords)?;
+        let image = ImageOptions::new(width, height, &keywords)?;
+        keywords.finish()?;
+        let frame = self.frame(py, &camera, &options, &image)?;
+        Ok(PyBytes::new(py, &frame.to_rgb8(&image.background)).into_py(py))
+    }
+
+    #[args(format = "\"rows\"", keywords = "**")]
+    fn render_photo_data(
+        &self,
+        py: Python,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        format: &str,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<PyObject> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::raster(&keywords)?;
+        let format = PhotoFormat::parse(format)?;
+        let image = ImageOptions::new(width, height, &keywords)?;
+        keywords.finish()?;
+        let frame = self.frame(py, &camera, &options, &image)?;
+        Ok(format.convert(py, &frame, &image.background))
+    }
+
+    #[args(
+        mode = "\"ascii\"",
+        ramp = "\".,-~:;=!*#$@\"",
+        background = "None",
+        keywords = "**"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn render_text(
+        &self,
+        py: Python,
+        camera: Camera,
+        columns: usize,
+        rows: usize,
+        mode: &str,
+        ramp: &str,
+        background: Option<Vec<f64>>,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<String> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::raster(&keywords)?;
+        let mode = TextMode::parse(mode)?;
+        let image = ImageOptions {
+            background: background
+                .as_ref()
+                .map_or_else(Vector3::zeros, |background| {
+                    Vector3::new(background[0], background[1], background[2])
+                }),
+            aspect: Some(mode.aspect()),
+            ..ImageOptions::new(columns, rows * mode.rows(), &keywords)?
+        };
+        keywords.finish()?;
+        let frame = self.frame(py, &camera, &options, &image)?;
+        Ok(mode.draw(&frame, ramp, background.map(|_| &image.background)))
+    }
+
+    #[args(alpha = false, keywords = "**")]
+    #[allow(clippy::too_many_arguments)]
+    fn render_to_file(
+        &self,
+        py: Python,
+        path: &str,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        alpha: bool,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<()> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::raster(&keywords)?;
+        let path = Path::new(path);
+        let format = ImageFormat::parse(path, alpha)?;
+        let image = ImageOptions::new(width, height, &keywords)?;
+        keywords.finish()?;
+        let frame = self.frame(py, &camera, &options, &image)?;
+        format.save(path, &frame, &image.background, alpha)
+    }
+
+    #[args(
+        background = "None",
+        stroke = "None",
+        stroke_width = "1.0",
+        keywords = "**"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn render_svg(
+        &self,
+        py: Python,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        background: Option<Vec<f64>>,
+        stroke: Option<Vec<f64>>,
+        stroke_width: f64,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<String> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::new(&keywords)?;
+        keywords.finish()?;
+        let shaded = self.paint(py, &camera, &options)?;
+        Ok(to_svg(
+            shaded,
+            &camera,
+            width,
+            height,
+            background,
+            stroke.map(|stroke| (stroke, stroke_width)),
+            options.colormap.as_ref(),
+        ))
+    }
+
+    #[args(canvas = "None", outline = "None", keywords = "**")]
+    #[allow(clippy::too_many_arguments)]
+    fn render_canvas(
+        &self,
+        py: Python,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        canvas: Option<&str>,
+        outline: Option<Vec<f64>>,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<PyObject> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::new(&keywords)?;
+        keywords.finish()?;
+        let shaded = self.paint(py, &camera, &options)?;
+        Ok(to_canvas(
+            py,
+            shaded,
+            &camera,
+            width,
+            height,
+            canvas,
+            outline,
+            options.colormap.as_ref(),
+        ))
+    }
+}
+
+/// A mesh being loaded on another thread. The progress callback only ever runs
+/// in the thread polling, so it can safely touch the UI.
+#[pyclass]
+struct MeshLoader {
+    progress: Arc<Progress>,
+    /// Gone once the mesh has been taken.
+    thread: Option<JoinHandle<PyResult<Mesh>>>,
+    callback: Option<PyObject>,
+    /// Bytes parsed and faces built the callback was last told about.
+    reported: Option<(usize, usize)>,
+}
+
+impl MeshLoader {
+    fn report(&mut self, py: Python) -> PyResult<()> {
+        let current = (self.bytes_parsed(), self.faces_built());
+        if let Some(callback) = &self.callback {
+            if self.reported != Some(current) {
+                self.reported = Some(current);
+                callback.call1(py, current)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Nobody can take the mesh anymore, so the thread stops instead of parsing the rest of the file.
+impl Drop for MeshLoader {
+    fn drop(&mut self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+}
+
+#[pymethods]
+impl MeshLoader {
+    #[getter]
+    fn bytes_parsed(&self) -> usize {
+        self.progress.bytes.load(Ordering::Relaxed)
+    }
+
+    #[getter]
+    fn total_bytes(&self) -> usize {
+        self.progress.total.load(Ordering::Relaxed)
+    }
+
+    #[getter]
+    fn faces_built(&self) -> usize {
+        self.progress.faces.load(Ordering::Relaxed)
+    }
+
+    /// Passes the progress on to the callback and tells whether loading has finished.
+    fn poll(&mut self, py: Python) -> PyResult<bool> {
+        self.report(py)?;
+        Ok(self
+            .thread
+            .as_ref()
+            .is_none_or(|thread| thread.is_finished()))
+    }
+
+    fn cancel(&self) {
+        self.progress.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Waits for loading to finish and hands over the mesh, raising whatever went wrong.
+    fn result(&mut self, py: Python) -> PyResult<Mesh> {
+        let thread = self
+            .thread
+            .take()
+            .ok_or_else(|| PyRuntimeError::new_err("the mesh has already been taken"))?;
+        let mesh = py
+            .allow_threads(|| thread.join())
+            .map_err(|_| PyRuntimeError::new_err("the thread loading the mesh panicked"))?;
+        self.report(py)?;
+        mesh
+    }
+}
+
+#[pyclass]
+struct Scene {
+    meshes: Vec<(Py<Mesh>, Transform)>,
+}
+
+impl Scene {
+    /// Every polygon of every mesh moved into place, with the materials of all meshes.
+    /// Stays in single precision when every mesh is stored in it.
+    fn gather(&self, py: Python) -> PyResult<(Geometry, Vec<Material>)> {
+        // meshes get changed without the GIL, so another thread may be rotating one right now
+        let borrowed = self
+            .meshes
+            .iter()
+            .map(|(mesh, transform)| {
+                let mesh = mesh.try_borrow(py).map_err(|_| {
+                    PyRuntimeError::new_err(
+                        "a mesh of the scene is being changed by another thread",
+                    )
+                })?;
+                Ok((mesh, transform))
             })
-            .collect::<Vec<(Vec<Vec<f64>>, f64)>>();
-        if !disable_occlusion {
-            culled.sort_by_cached_key(|(poly, _)| {
-                poly.iter().map(|point| -point[2]).sum::<f64>().round() as i64
+            .collect::<PyResult<Vec<(PyRef<Mesh>, &Transform)>>>()?;
+        let meshes = borrowed
+            .iter()
+            .map(|(mesh, transform)| (&**mesh, *transform))
+            .collect::<Vec<(&Mesh, &Transform)>>();
+        Ok(py.allow_threads(|| {
+            let mut materials = Vec::new();
+            let offsets = meshes
+                .iter()
+                .map(|(mesh, _)| {
+                    let offset = materials.len();
+                    materials.extend(mesh.materials.iter().cloned());
+                    offset
+                })
+                .collect::<Vec<usize>>();
+            let single = meshes
+                .iter()
+                .all(|(mesh, _)| matches!(mesh.geometry, Geometry::Single(_)));
+            let geometry = if single {
+                Geometry::Single(Self::place(&meshes, &offsets))
+            } else {
+                Geometry::Double(Self::place(&meshes, &offsets))
+            };
+            (geometry, materials)
+        }))
+    }
+
+    /// Moves the polygons of all meshes into place in the precision `T`,
+    /// pointing them at the materials starting at the offset of their mesh.
+    fn place<T: Real + Send>(meshes: &[(&Mesh, &Transform)], offsets: &[usize]) -> Vec<Polygon<T>> {
+        let mut polygons = Vec::new();
+        for ((mesh, transform), offset) in meshes.iter().zip(offsets) {
+            with_polygons!(&mesh.geometry, mesh_polygons => {
+                polygons.par_extend(mesh_polygons.par_iter().map(|polygon| {
+                    let mut polygon = transform.apply(polygon);
+                    polygon.material += offset;
+                    polygon
+                }))
             });
-        }    
-        
-        Ok(culled)
+        }
+        polygons
+    }
+
+    /// Shades and orders the polygons of all meshes for the painter's algorithm, see `render`.
+    fn paint(&self, py: Python, camera: &Camera, options: &RenderOptions) -> PyResult<Vec<Shaded>> {
+        if options.visibility == Visibility::Bsp {
+            return Err(PyValueError::new_err(
+                "scenes can't use visibility 'bsp', use 'sort' or 'newell'",
+            ));
+        }
+        let (geometry, materials) = self.gather(py)?;
+        py.allow_threads(|| {
+            with_polygons!(&geometry, polygons => render(polygons, &materials, None, camera, options))
+        })
+    }
+
+    /// Rasterizes the polygons of all meshes into a frame, see `rasterize`.
+    fn frame(
+        &self,
+        py: Python,
+        camera: &Camera,
+        options: &RenderOptions,
+        image: &ImageOptions,
+    ) -> PyResult<raster::Frame> {
+        let (geometry, materials) = self.gather(py)?;
+        Ok(py.allow_threads(|| {
+            with_polygons!(&geometry, polygons => {
+                rasterize(polygons, &materials, camera, options, image)
+            })
+        }))
+    }
+}
+
+#[pymethods]
+impl Scene {
+    #[new]
+    fn new() -> Self {
+        Self { meshes: Vec::new() }
+    }
+
+    #[args(
+        position = "vec![0.0, 0.0, 0.0]",
+        rotation = "vec![0.0, 0.0, 0.0]",
+        scale = "1.0"
+    )]
+    fn add(&mut self, mesh: Py<Mesh>, position: Vec<f64>, rotation: Vec<f64>, scale: f64) -> usize {
+        self.meshes
+            .push((mesh, Transform::new(position, rotation, scale)));
+        self.meshes.len() - 1
+    }
+
+    #[args(
+        position = "vec![0.0, 0.0, 0.0]",
+        rotation = "vec![0.0, 0.0, 0.0]",
+        scale = "1.0"
+    )]
+    fn set_transform(
+        &mut self,
+        index: usize,
+        position: Vec<f64>,
+        rotation: Vec<f64>,
+        scale: f64,
+    ) -> PyResult<()> {
+        let (_, transform) = self
+            .meshes
+            .get_mut(index)
+            .ok_or_else(|| PyIndexError::new_err("scene index out of range"))?;
+        *transform = Transform::new(position, rotation, scale);
+        Ok(())
+    }
+
+    fn __len__(&self) -> usize {
+        self.meshes.len()
+    }
+
+    #[args(
+        culling = "\"back\"",
+        boundary = false,
+        crease = "None",
+        silhouette = false,
+        remove_hidden = false
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn get_edges(
+        &self,
+        py: Python,
+        camera: Camera,
+        culling: &str,
+        boundary: bool,
+        crease: Option<f64>,
+        silhouette: bool,
+        remove_hidden: bool,
+    ) -> PyResult<Vec<Vec<Vec<f64>>>> {
+        let filter = EdgeFilter {
+            culling: Culling::parse(culling, false)?,
+            boundary,
+            crease,
+            silhouette,
+        };
+        let (geometry, _) = self.gather(py)?;
+        Ok(py.allow_threads(|| {
+            with_polygons!(&geometry, polygons => {
+                project_edges(polygons, &camera, &filter, remove_hidden)
+            })
+        }))
+    }
+
+    #[args(output = "\"shade\"", keywords = "**")]
+    fn render(
+        &self,
+        py: Python,
+        camera: Camera,
+        output: &str,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<Vec<(Vec<Vec<f64>>, PyObject)>> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::new(&keywords)?;
+        keywords.finish()?;
+        let output = Output::parse(output)?;
+        let shaded = self.paint(py, &camera, &options)?;
+        Ok(output.convert(py, shaded, options.colormap.as_ref()))
+    }
+
+    #[args(keywords = "**")]
+    fn render_image(
+        &self,
+        py: Python,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<PyObject> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::raster(&keywords)?;
+        let image = ImageOptions::new(width, height, &keywords)?;
+        keywords.finish()?;
+        let frame = self.frame(py, &camera, &options, &image)?;
+        Ok(PyBytes::new(py, &frame.to_rgb8(&image.background)).into_py(py))
+    }
+
+    #[args(format = "\"rows\"", keywords = "**")]
+    fn render_photo_data(
+        &self,
+        py: Python,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        format: &str,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<PyObject> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::raster(&keywords)?;
+        let format = PhotoFormat::parse(format)?;
+        let image = ImageOptions::new(width, height, &keywords)?;
+        keywords.finish()?;
+        let frame = self.frame(py, &camera, &options, &image)?;
+        Ok(format.convert(py, &frame, &image.background))
+    }
+
+    #[args(
+        mode = "\"ascii\"",
+        ramp = "\".,-~:;=!*#$@\"",
+        background = "None",
+        keywords = "**"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn render_text(
+        &self,
+        py: Python,
+        camera: Camera,
+        columns: usize,
+        rows: usize,
+        mode: &str,
+        ramp: &str,
+        background: Option<Vec<f64>>,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<String> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::raster(&keywords)?;
+        let mode = TextMode::parse(mode)?;
+        let image = ImageOptions {
+            background: background
+                .as_ref()
+                .map_or_else(Vector3::zeros, |background| {
+                    Vector3::new(background[0], background[1], background[2])
+                }),
+            aspect: Some(mode.aspect()),
+            ..ImageOptions::new(columns, rows * mode.rows(), &keywords)?
+        };
+        keywords.finish()?;
+        let frame = self.frame(py, &camera, &options, &image)?;
+        Ok(mode.draw(&frame, ramp, background.map(|_| &image.background)))
+    }
+
+    #[args(alpha = false, keywords = "**")]
+    #[allow(clippy::too_many_arguments)]
+    fn render_to_file(
+        &self,
+        py: Python,
+        path: &str,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        alpha: bool,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<()> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::raster(&keywords)?;
+        let path = Path::new(path);
+        let format = ImageFormat::parse(path, alpha)?;
+        let image = ImageOptions::new(width, height, &keywords)?;
+        keywords.finish()?;
+        let frame = self.frame(py, &camera, &options, &image)?;
+        format.save(path, &frame, &image.background, alpha)
+    }
+
+    #[args(
+        background = "None",
+        stroke = "None",
+        stroke_width = "1.0",
+        keywords = "**"
+    )]
+    #[allow(clippy::too_many_arguments)]
+    fn render_svg(
+        &self,
+        py: Python,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        background: Option<Vec<f64>>,
+        stroke: Option<Vec<f64>>,
+        stroke_width: f64,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<String> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::new(&keywords)?;
+        keywords.finish()?;
+        let shaded = self.paint(py, &camera, &options)?;
+        Ok(to_svg(
+            shaded,
+            &camera,
+            width,
+            height,
+            background,
+            stroke.map(|stroke| (stroke, stroke_width)),
+            options.colormap.as_ref(),
+        ))
+    }
+
+    #[args(canvas = "None", outline = "None", keywords = "**")]
+    #[allow(clippy::too_many_arguments)]
+    fn render_canvas(
+        &self,
+        py: Python,
+        camera: Camera,
+        width: usize,
+        height: usize,
+        canvas: Option<&str>,
+        outline: Option<Vec<f64>>,
+        keywords: Option<&PyDict>,
+    ) -> PyResult<PyObject> {
+        let keywords = Keywords::new(keywords)?;
+        let options = RenderOptions::new(&keywords)?;
+        keywords.finish()?;
+        let shaded = self.paint(py, &camera, &options)?;
+        Ok(to_canvas(
+            py,
+            shaded,
+            &camera,
+            width,
+            height,
+            canvas,
+            outline,
+            options.colormap.as_ref(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    /// The cube from -1 to 1 as 12 triangles wound counterclockwise seen from outside.
+    fn cube() -> Vec<Polygon> {
+        let mut polygons = Vec::new();
+        for axis in 0..3 {
+            for sign in [-1.0, 1.0] {
+                let normal = Vector3::ith(axis, sign);
+                let (u, v) = (
+                    Vector3::ith((axis + 1) % 3, 1.0),
+                    Vector3::ith((axis + 2) % 3, 1.0),
+                );
+                let (u, v) = if sign > 0.0 { (u, v) } else { (v, u) };
+                let corners = [
+                    normal - u - v,
+                    normal + u - v,
+                    normal + u + v,
+                    normal - u + v,
+                ];
+                for triangle in [[0, 1, 2], [0, 2, 3]] {
+                    let points = triangle.map(|i| corners[i]).to_vec();
+                    polygons.push(Polygon::new(points, vec![normal; 3]));
+                }
+            }
+        }
+        polygons
+    }
+
+    fn edges(eye: [f64; 3], filter: EdgeFilter, remove_hidden: bool) -> Vec<Vec<Vec<f64>>> {
+        let camera = Camera::new(vec![0.0, 0.0, 1.0], eye.to_vec(), 20.0);
+        project_edges(&cube(), &camera, &filter, remove_hidden)
+    }
+
+    fn filter(culling: Culling, crease: Option<f64>, silhouette: bool) -> EdgeFilter {
+        EdgeFilter {
+            culling,
+            boundary: false,
+            crease,
+            silhouette,
+        }
+    }
+
+    #[test]
+    fn cube_faces_point_outwards() {
+        let eye = Vector3::new(0.0, 0.0, -5.0);
+        let facing = cube().iter().filter(|polygon| polygon.faces(&eye)).count();
+        assert_eq!(facing, 2);
+    }
+
+    #[test]
+    fn creases_are_the_cube_edges_above_the_angle() {
+        let eye = [0.0, 0.0, -5.0];
+        // the diagonals splitting each face are flat, the 12 cube edges are right angles
+        assert_eq!(
+            edges(eye, filter(Culling::None, None, false), false).len(),
+            18
+        );
+        assert_eq!(
+            edges(eye, filter(Culling::None, Some(PI / 4.0), false), false).len(),
+            12
+        );
+        assert!(edges(eye, filter(Culling::None, Some(PI * 0.6), false), false).is_empty());
+        // only the front face is left after culling, the creases around it stay
+        assert_eq!(
+            edges(eye, filter(Culling::Back, Some(PI / 4.0), false), false).len(),
+            4
+        );
+    }
+
+    #[test]
+    fn silhouette_outlines_the_cube_from_a_corner() {
+        // three faces are visible, their outline is a hexagon
+        let silhouette = edges([4.0, 4.0, -4.0], filter(Culling::None, None, true), false);
+        assert_eq!(silhouette.len(), 6);
+    }
+
+    #[test]
+    fn hidden_lines_behind_the_front_face_get_removed() {
+        let on_face = |segments: &[Vec<Vec<f64>>], z: f64| {
+            segments
+                .iter()
+                .filter(|segment| segment.iter().all(|point| point[2] == z))
+                .count()
+        };
+        let eye = [0.0, 0.0, -5.0];
+        let all = edges(eye, filter(Culling::None, None, false), false);
+        assert_eq!(on_face(&all, 1.0), 5);
+        let visible = edges(eye, filter(Culling::None, None, false), true);
+        // the back face lies right behind the front one, which stays whole
+        assert_eq!(on_face(&visible, 1.0), 0);
+        assert_eq!(on_face(&visible, -1.0), 5);
+    }
+
+    /// A square facing the camera drawn as text, the way render_text draws meshes.
+    fn square_text(mode: TextMode, columns: usize, rows: usize) -> String {
+        let square = Polygon::new(
+            vec![
+                Vector3::new(-1.0, -1.0, 0.0),
+                Vector3::new(1.0, -1.0, 0.0),
+                Vector3::new(1.0, 1.0, 0.0),
+                Vector3::new(-1.0, 1.0, 0.0),
+            ],
+            vec![-Vector3::z(); 4],
+        );
+        // the square spans half of the camera range
+        let camera = Camera::new(vec![0.0, 0.0, 1.0], vec![0.0, 0.0, -10.0], 0.4);
+        let options = RenderOptions {
+            culling: Culling::None,
+            sort: SortKey::Centroid,
+            visibility: Visibility::Sort,
+            occlusion: true,
+            lights: RenderOptions::lights(None, None),
+            shading: Shading::Flat,
+            material: None,
+            fog: None,
+            // Toon::default goes through the Python constructor, which tests can't link against
+            toon: Toon {
+                thresholds: Vec::new(),
+                outline: 0.0,
+                outline_color: Vector3::zeros(),
+            },
+            shadows: false,
+            colormap: None,
+        };
+        let image = ImageOptions {
+            width: columns,
+            height: rows * mode.rows(),
+            background: Vector3::zeros(),
+            samples: 1,
+            filter: Filter::Box,
+            sampling: Sampling::Nearest,
+            aspect: Some(mode.aspect()),
+        };
+        let frame = rasterize(&[square], &[Material::default()], &camera, &options, &image);
+        mode.draw(&frame, "#", None)
+    }
+
+    #[test]
+    fn text_keeps_the_proportions_of_tall_cells() {
+        // cells are twice as tall as wide, so a square takes half as many rows as columns
+        let ascii = square_text(TextMode::Ascii, 8, 8);
+        let lines = ascii.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 8);
+        assert!(lines.iter().all(|line| line.chars().count() == 8));
+        let covered = lines
+            .iter()
+            .filter(|line| line.contains('#'))
+            .collect::<Vec<_>>();
+        assert_eq!(covered, vec![&"  ####  "; 2]);
+
+        let blocks = square_text(TextMode::Blocks, 8, 8);
+        let lines = blocks.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 8);
+        let covered = lines
+            .iter()
+            .filter(|line| line.contains('\u{2580}'))
+            .count();
+        assert_eq!(covered, 2);
+        assert!(lines
+            .iter()
+            .all(|line| line.matches('\u{2580}').count() % 4 == 0));
     }
 }
@@ -0,0 +1,71 @@
+use nalgebra::Vector3;
+
+/// Where the light of a `Light` comes from, in world space.
+#[derive(Clone)]
+pub enum Source {
+    Ambient,
+    Directional {
+        direction: Vector3<f64>,
+    },
+    Point {
+        position: Vector3<f64>,
+        attenuation: Vector3<f64>,
+    },
+    Spot {
+        position: Vector3<f64>,
+        direction: Vector3<f64>,
+        cos_inner: f64,
+        cos_outer: f64,
+        attenuation: Vector3<f64>,
+    },
+}
+
+/// Constant, linear and quadratic falloff with distance.
+fn attenuate(attenuation: &Vector3<f64>, distance: f64) -> f64 {
+    1.0 / (attenuation[0] + attenuation[1] * distance + attenuation[2] * distance * distance)
+        .max(f64::EPSILON)
+}
+
+fn smoothstep(edge0: f64, edge1: f64, x: f64) -> f64 {
+    if edge1 <= edge0 {
+        return if x >= edge1 { 1.0 } else { 0.0 };
+    }
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
+
+impl Source {
+    /// Unit vector from `point` towards the light, how much of the light reaches it and how far
+    /// away the light is, or `None` for ambient light which comes from everywhere.
+    pub fn incident(&self, point: &Vector3<f64>) -> Option<(Vector3<f64>, f64, f64)> {
+        match self {
+            Source::Ambient => None,
+            Source::Directional { direction } => Some((-direction, 1.0, f64::INFINITY)),
+            Source::Point {
+                position,
+                attenuation,
+            } => {
+                let towards = position - point;
+                let distance = towards.norm();
+                Some((
+                    towards / distance,
+                    attenuate(attenuation, distance),
+                    distance,
+                ))
+            }
+            Source::Spot {
+                position,
+                direction,
+                cos_inner,
+                cos_outer,
+                attenuation,
+            } => {
+                let towards = position - point;
+                let distance = towards.norm();
+                let towards = towards / distance;
+                let cone = smoothstep(*cos_outer, *cos_inner, -towards.dot(direction));
+                Some((towards, cone * attenuate(attenuation, distance), distance))
+            }
+        }
+    }
+}
@@ -0,0 +1,304 @@
+use crate::bsp::Bsp;
+use crate::plane::{Plane, EPSILON};
+use nalgebra::{Vector2, Vector3};
+
+struct Piece {
+    source: usize,
+    points: Vec<Vector3<f64>>,
+    plane: Plane,
+    projected: Vec<Vector2<f64>>,
+    z_min: f64,
+    z_max: f64,
+    moved: bool,
+}
+
+impl Piece {
+    fn new(source: usize, points: Vec<Vector3<f64>>) -> Self {
+        let plane = Plane::new(&points);
+        let projected = points
+            .iter()
+            .map(|point| {
+                let z = point.z.max(EPSILON);
+                Vector2::new(point.x / z, point.y / z)
+            })
+            .collect();
+        let z_min = points
+            .iter()
+            .map(|point| point.z)
+            .fold(f64::INFINITY, f64::min);
+        let z_max = points
+            .iter()
+            .map(|point| point.z)
+            .fold(f64::NEG_INFINITY, f64::max);
+        Self {
+            source,
+            points,
+            plane,
+            projected,
+            z_min,
+            z_max,
+            moved: false,
+        }
+    }
+
+    fn extent(&self, axis: usize) -> (f64, f64) {
+        self.projected
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), point| {
+                (min.min(point[axis]), max.max(point[axis]))
+            })
+    }
+
+    /// Whether painting `self` before `other` can never hide any part of `other`.
+    fn paints_under(&self, other: &Piece) -> bool {
+        if other.z_max <= self.z_min + EPSILON {
+            return true;
+        }
+        let (self_x_min, self_x_max) = self.extent(0);
+        let (other_x_min, other_x_max) = other.extent(0);
+        if self_x_max <= other_x_min + EPSILON || other_x_max <= self_x_min + EPSILON {
+            return true;
+        }
+        let (self_y_min, self_y_max) = self.extent(1);
+        let (other_y_min, other_y_max) = other.extent(1);
+        if self_y_max <= other_y_min + EPSILON || other_y_max <= self_y_min + EPSILON {
+            return true;
+        }
+        other.plane.behind(&self.points)
+            || self.plane.in_front(&other.points)
+            || !overlaps(&self.projected, &other.projected)
+    }
+}
+
+/// Separating axis test between two convex polygons, touching edges don't count as overlap.
+fn overlaps(a: &[Vector2<f64>], b: &[Vector2<f64>]) -> bool {
+    fn separated(a: &[Vector2<f64>], b: &[Vector2<f64>]) -> bool {
+        a.iter().enumerate().any(|(i, start)| {
+            let edge = a[(i + 1) % a.len()] - start;
+            let axis = Vector2::new(-edge.y, edge.x);
+            if axis.norm_squared() < EPSILON * EPSILON {
+                return false;
+            }
+            let project = |points: &[Vector2<f64>]| {
+                points
+                    .iter()
+                    .map(|point| axis.dot(point))
+                    .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), d| {
+                        (min.min(d), max.max(d))
+                    })
+            };
+            let (a_min, a_max) = project(a);
+            let (b_min, b_max) = project(b);
+            let tolerance = EPSILON * axis.norm();
+            a_max <= b_min + tolerance || b_max <= a_min + tolerance
+        })
+    }
+    !(separated(a, b) || separated(b, a))
+}
+
+/// Orders view space polygons back to front with the Newell–Newell–Sancha algorithm,
+/// splitting polygons that overlap cyclically or intersect each other. Should a cycle
+/// survive the splits, a BSP tree orders whatever is left instead.
+///
+/// Returns the index of the polygon each piece was cut from along with its points.
+pub fn order(polygons: Vec<Vec<Vector3<f64>>>) -> Vec<(usize, Vec<Vector3<f64>>)> {
+    let splits = 4 * polygons.len() + 64;
+    order_within(polygons, splits)
+}
+
+/// Orders polygons like `order`, giving up on splitting after `splits_left` cuts.
+fn order_within(
+    polygons: Vec<Vec<Vector3<f64>>>,
+    mut splits_left: usize,
+) -> Vec<(usize, Vec<Vector3<f64>>)> {
+    // the farthest polygon is kept at the end, so it can be popped off
+    let mut pending = polygons
+        .into_iter()
+        .enumerate()
+        .map(|(source, points)| Piece::new(source, points))
+        .collect::<Vec<Piece>>();
+    pending.sort_by(|a, b| a.z_max.total_cmp(&b.z_max));
+
+    let mut ordered = Vec::with_capacity(pending.len());
+    while let Some(last) = pending.len().checked_sub(1) {
+        let p = &pending[last];
+        let conflict = pending[..last].iter().position(|q| !p.paints_under(q));
+        let Some(index) = conflict else {
+            let p = pending.pop().unwrap();
+            ordered.push((p.source, p.points));
+            continue;
+        };
+
+        let q = &pending[index];
+        let swappable = p.plane.behind(&q.points) || q.plane.in_front(&p.points);
+        if swappable && !q.moved {
+            let mut q = pending.remove(index);
+            q.moved = true;
+            pending.push(q);
+            continue;
+        }
+
+        // cyclic overlap or intersecting polygons, cut one of them along the plane of the other
+        let split = if splits_left == 0 {
+            None
+        } else if q.plane.crosses(&p.points) {
+            Some((last, q.plane))
+        } else if p.plane.crosses(&q.points) {
+            Some((index, p.plane))
+        } else {
+            None
+        };
+        let Some((target, plane)) = split else {
+            // a cycle no cut along either plane breaks, or too many cuts already,
+            // a BSP tree orders whatever is left from the camera at the origin
+            let rest = pending
+                .into_iter()
+                .map(|piece| (piece.source, piece.points))
+                .collect();
+            ordered.extend(Bsp::new(rest).order(&Vector3::zeros()).into_iter().cloned());
+            return ordered;
+        };
+        splits_left -= 1;
+        let piece = pending.remove(target);
+        let (front, back) = plane.split(&piece.points);
+        for points in [front, back] {
+            if points.len() >= 3 {
+                let piece = Piece::new(piece.source, points);
+                let at = pending.partition_point(|other| other.z_max <= piece.z_max);
+                pending.insert(at, piece);
+            }
+        }
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn area(points: &[Vector3<f64>]) -> f64 {
+        (1..points.len() - 1)
+            .map(|i| {
+                (points[i] - points[0])
+                    .cross(&(points[i + 1] - points[0]))
+                    .norm()
+                    / 2.0
+            })
+            .sum()
+    }
+
+    /// The depth where the view ray through `(x, y)` hits a convex polygon, if it does.
+    fn depth(points: &[Vector3<f64>], x: f64, y: f64) -> Option<f64> {
+        let piece = Piece::new(0, points.to_vec());
+        let along = piece.plane.normal.dot(&Vector3::new(x, y, 1.0));
+        let sides = piece
+            .projected
+            .iter()
+            .enumerate()
+            .map(|(i, a)| {
+                let b = piece.projected[(i + 1) % piece.projected.len()];
+                (b - a).perp(&(Vector2::new(x, y) - a))
+            })
+            .collect::<Vec<f64>>();
+        let inside = sides.iter().all(|side| *side > 0.0) || sides.iter().all(|side| *side < 0.0);
+        (inside && along.abs() > EPSILON).then(|| -piece.plane.offset / along)
+    }
+
+    /// Casts view rays over a grid, the nearest piece has to end up on top along each.
+    fn assert_paints_in_order(ordered: &[(usize, Vec<Vector3<f64>>)], extent: f64) {
+        let mut overlapping = 0;
+        for i in 0..64 {
+            for j in 0..64 {
+                // odd steps, so no ray runs exactly along an edge
+                let step = 2.0 * extent / 63.0 + 1e-4;
+                let (x, y) = (-extent + i as f64 * step, -extent + j as f64 * step);
+                let depths = ordered
+                    .iter()
+                    .filter_map(|(_, points)| depth(points, x, y))
+                    .collect::<Vec<f64>>();
+                for pair in depths.windows(2) {
+                    assert!(pair[1] <= pair[0] + 1e-9, "{:?} at ({x}, {y})", depths);
+                }
+                overlapping += (depths.len() > 1) as usize;
+            }
+        }
+        assert!(overlapping > 0);
+    }
+
+    /// Three thin sticks tilted in depth, each lying over the next where they cross.
+    fn pinwheel() -> Vec<Vec<Vector3<f64>>> {
+        (0..3)
+            .map(|i| {
+                let angle = i as f64 * std::f64::consts::TAU / 3.0;
+                let along = Vector2::new(angle.cos(), angle.sin());
+                let across = Vector2::new(-along.y, along.x);
+                [(-1.0, -0.05), (1.0, -0.05), (1.0, 0.05), (-1.0, 0.05)]
+                    .iter()
+                    .map(|&(t, w)| {
+                        let xy = across * (0.3 + w) + along * t;
+                        Vector3::new(xy.x, xy.y, 5.0 + 0.2 * t)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn orders_separate_polygons_back_to_front() {
+        let near = vec![
+            Vector3::new(-1.0, -1.0, 2.0),
+            Vector3::new(1.0, -1.0, 2.0),
+            Vector3::new(0.0, 1.0, 2.0),
+        ];
+        let far = near.iter().map(|point| point + Vector3::z()).collect();
+        let ordered = order(vec![near, far]);
+        let sources = ordered
+            .iter()
+            .map(|(source, _)| *source)
+            .collect::<Vec<usize>>();
+        assert_eq!(sources, vec![1, 0]);
+    }
+
+    #[test]
+    fn splits_interpenetrating_triangles() {
+        // tilted against each other so they cut through one another along y == 0
+        let triangles = vec![
+            vec![
+                Vector3::new(-1.0, -1.0, 4.0),
+                Vector3::new(1.0, -1.0, 4.0),
+                Vector3::new(0.0, 1.0, 6.0),
+            ],
+            vec![
+                Vector3::new(-1.0, 1.0, 4.0),
+                Vector3::new(1.0, 1.0, 4.0),
+                Vector3::new(0.0, -1.0, 6.0),
+            ],
+        ];
+        let ordered = order(triangles.clone());
+        // one triangle gets cut along the plane of the other, the pieces go on either side of it
+        assert_eq!(ordered.len(), 3);
+        for (source, triangle) in triangles.iter().enumerate() {
+            let covered = ordered
+                .iter()
+                .filter(|(piece, _)| *piece == source)
+                .map(|(_, points)| area(points))
+                .sum::<f64>();
+            assert!((covered - area(triangle)).abs() < EPSILON);
+        }
+
+        assert_paints_in_order(&ordered, 0.4);
+    }
+
+    #[test]
+    fn splits_cyclic_overlap() {
+        let ordered = order(pinwheel());
+        assert!(ordered.len() > 3);
+        assert_paints_in_order(&ordered, 0.3);
+    }
+
+    #[test]
+    fn falls_back_to_bsp_without_splits() {
+        let ordered = order_within(pinwheel(), 0);
+        assert_paints_in_order(&ordered, 0.3);
+    }
+}
@@ -0,0 +1,153 @@
+use nalgebra::Vector3;
+
+pub const EPSILON: f64 = 1e-9;
+
+/// The plane through a polygon, `normal.dot(point) + offset == 0`.
+#[derive(Clone, Copy)]
+pub struct Plane {
+    pub normal: Vector3<f64>,
+    pub offset: f64,
+}
+
+impl Plane {
+    pub fn new(points: &[Vector3<f64>]) -> Self {
+        // Newell's method, robust for slightly non planar and degenerate polygons
+        let mut normal = Vector3::zeros();
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            normal.x += (a.y - b.y) * (a.z + b.z);
+            normal.y += (a.z - b.z) * (a.x + b.x);
+            normal.z += (a.x - b.x) * (a.y + b.y);
+        }
+        let norm = normal.norm();
+        if norm > 0.0 {
+            normal /= norm;
+        }
+        let centroid = points.iter().sum::<Vector3<f64>>() / points.len() as f64;
+        Self {
+            normal,
+            offset: -normal.dot(&centroid),
+        }
+    }
+
+    pub fn distance(&self, point: &Vector3<f64>) -> f64 {
+        self.normal.dot(point) + self.offset
+    }
+
+    /// Which side of the plane the camera sits on when it is placed at the view space origin.
+    pub fn eye_side(&self) -> f64 {
+        if self.offset.abs() < EPSILON {
+            0.0
+        } else {
+            self.offset.signum()
+        }
+    }
+
+    /// Whether all of `points` lie on the side facing away from the camera, or on the plane.
+    pub fn behind(&self, points: &[Vector3<f64>]) -> bool {
+        let side = self.eye_side();
+        points
+            .iter()
+            .all(|point| self.distance(point) * side <= EPSILON)
+    }
+
+    /// Whether all of `points` lie on the same side as the camera, or on the plane.
+    pub fn in_front(&self, points: &[Vector3<f64>]) -> bool {
+        let side = self.eye_side();
+        points
+            .iter()
+            .all(|point| self.distance(point) * side >= -EPSILON)
+    }
+
+    /// Whether the plane cuts through `points`, leaving vertices strictly on both sides.
+    pub fn crosses(&self, points: &[Vector3<f64>]) -> bool {
+        let mut front = false;
+        let mut back = false;
+        for point in points {
+            let distance = self.distance(point);
+            front |= distance > EPSILON;
+            back |= distance < -EPSILON;
+        }
+        front && back
+    }
+
+    /// Splits a convex polygon in two along the plane, returning the part on the
+    /// positive side of the normal first.
+    pub fn split(&self, points: &[Vector3<f64>]) -> (Vec<Vector3<f64>>, Vec<Vector3<f64>>) {
+        let mut front = Vec::new();
+        let mut back = Vec::new();
+        for (i, a) in points.iter().enumerate() {
+            let b = points[(i + 1) % points.len()];
+            let distance_a = self.distance(a);
+            let distance_b = self.distance(&b);
+            if distance_a >= -EPSILON {
+                front.push(*a);
+            }
+            if distance_a <= EPSILON {
+                back.push(*a);
+            }
+            if (distance_a > EPSILON && distance_b < -EPSILON)
+                || (distance_a < -EPSILON && distance_b > EPSILON)
+            {
+                let t = distance_a / (distance_a - distance_b);
+                let intersection = a + (b - a) * t;
+                front.push(intersection);
+                back.push(intersection);
+            }
+        }
+        (front, back)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plane_x() -> Plane {
+        Plane {
+            normal: Vector3::x(),
+            offset: 0.0,
+        }
+    }
+
+    #[test]
+    fn split_straddling_triangle() {
+        let triangle = [
+            Vector3::new(-1.0, 0.0, 0.0),
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+        ];
+        assert!(plane_x().crosses(&triangle));
+        let (front, back) = plane_x().split(&triangle);
+        assert_eq!(
+            front,
+            vec![
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(1.0, 1.0, 0.0),
+                Vector3::new(0.0, 0.5, 0.0),
+            ]
+        );
+        assert_eq!(
+            back,
+            vec![
+                Vector3::new(-1.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.0, 0.0),
+                Vector3::new(0.0, 0.5, 0.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_keeps_polygons_on_one_side_whole() {
+        let triangle = [
+            Vector3::new(1.0, 0.0, 0.0),
+            Vector3::new(2.0, 0.0, 0.0),
+            Vector3::new(1.0, 1.0, 0.0),
+        ];
+        assert!(!plane_x().crosses(&triangle));
+        let (front, back) = plane_x().split(&triangle);
+        assert_eq!(front, triangle.to_vec());
+        assert!(back.is_empty());
+    }
+}
@@ -0,0 +1,393 @@
+use crate::texture::Texture;
+use crate::{Sampling, Wrap};
+use nalgebra::{Vector2, Vector3, Vector4};
+use rayon::prelude::*;
+
+/// How many rows of pixels each parallel job rasterizes.
+const BAND: usize = 16;
+
+/// Triangles get clipped to this view space depth, anything closer would project to infinity.
+pub const NEAR: f64 = 1e-3;
+
+#[derive(Clone, Copy)]
+pub struct Vertex {
+    /// Pixel x and y, view space depth in z.
+    pub position: Vector3<f64>,
+    /// Light added on top of the texture, or the whole color without one.
+    pub color: Vector3<f64>,
+    /// Light the texture gets multiplied with.
+    pub tint: Vector3<f64>,
+    pub uv: Vector2<f64>,
+}
+
+impl Vertex {
+    fn lerp(&self, other: &Vertex, t: f64) -> Vertex {
+        Vertex {
+            position: self.position.lerp(&other.position, t),
+            color: self.color.lerp(&other.color, t),
+            tint: self.tint.lerp(&other.tint, t),
+            uv: self.uv.lerp(&other.uv, t),
+        }
+    }
+}
+
+pub struct Triangle<'a> {
+    pub vertices: [Vertex; 3],
+    pub texture: Option<(&'a Texture, Wrap)>,
+}
+
+/// An image with colors between 0 and 1, alpha being how much of each pixel is covered.
+/// Colors are premultiplied by alpha, so averaging pixels blends edges correctly.
+pub struct Frame {
+    pub width: usize,
+    pub height: usize,
+    pub pixels: Vec<Vector4<f64>>,
+}
+
+impl Frame {
+    /// The frame over a background color, 3 bytes per pixel row by row.
+    pub fn to_rgb8(&self, background: &Vector3<f64>) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| {
+                let color = pixel.xyz() + background * (1.0 - pixel[3]);
+                [0, 1, 2].map(|i| (color[i].clamp(0.0, 1.0) * 255.0).round() as u8)
+            })
+            .collect()
+    }
+
+    /// The frame with straight alpha, 4 bytes per pixel row by row.
+    pub fn to_rgba8(&self) -> Vec<u8> {
+        self.pixels
+            .iter()
+            .flat_map(|pixel| {
+                let alpha = pixel[3];
+                let color = if alpha > 0.0 {
+                    pixel.xyz() / alpha
+                } else {
+                    Vector3::zeros()
+                };
+                [color[0], color[1], color[2], alpha]
+                    .map(|channel| (channel.clamp(0.0, 1.0) * 255.0).round() as u8)
+            })
+            .collect()
+    }
+
+    /// Shrinks a frame rendered at `factor` times the size, weighting samples by their
+    /// distance in output pixels from each pixel center, out to `radius`.
+    pub fn downsample(
+        &self,
+        factor: usize,
+        radius: f64,
+        weight: impl Fn(f64) -> f64 + Sync,
+    ) -> Frame {
+        let width = self.width / factor;
+        let height = self.height / factor;
+        // the filter is separable, so the taps along one axis are the same for every pixel
+        let reach = (radius * factor as f64).ceil() as isize;
+        let taps = (-reach..reach + factor as isize)
+            .map(|offset| {
+                let distance = (offset as f64 + 0.5) / factor as f64 - 0.5;
+                (offset, weight(distance))
+            })
+            .filter(|(_, weight)| *weight > 0.0)
+            .collect::<Vec<(isize, f64)>>();
+        let mut pixels = vec![Vector4::zeros(); width * height];
+        pixels
+            .par_chunks_mut(width.max(1))
+            .enumerate()
+            .for_each(|(y, row)| {
+                for (x, pixel) in row.iter_mut().enumerate() {
+                    let mut sum = Vector4::zeros();
+                    let mut total = 0.0;
+                    for &(dy, wy) in &taps {
+                        let sy = (y * factor) as isize + dy;
+                        if sy < 0 || sy >= self.height as isize {
+                            continue;
+                        }
+                        for &(dx, wx) in &taps {
+                            let sx = (x * factor) as isize + dx;
+                            if sx < 0 || sx >= self.width as isize {
+                                continue;
+                            }
+                            sum += self.pixels[sy as usize * self.width + sx as usize] * wx * wy;
+                            total += wx * wy;
+                        }
+                    }
+                    if total > 0.0 {
+                        *pixel = sum / total;
+                    }
+                }
+            });
+        Frame {
+            width,
+            height,
+            pixels,
+        }
+    }
+}
+
+/// Clips a polygon with view space positions against the near plane,
+/// keeping the part in front of the camera.
+pub fn clip_near(vertices: &[Vertex]) -> Vec<Vertex> {
+    let mut clipped = Vec::with_capacity(vertices.len() + 1);
+    for (i, a) in vertices.iter().enumerate() {
+        let b = &vertices[(i + 1) % vertices.len()];
+        let (a_z, b_z) = (a.position.z, b.position.z);
+        if a_z >= NEAR {
+            clipped.push(*a);
+        }
+        if (a_z >= NEAR) != (b_z >= NEAR) {
+            clipped.push(a.lerp(b, (NEAR - a_z) / (b_z - a_z)));
+        }
+    }
+    clipped
+}
+
+fn edge(a: &Vector3<f64>, b: &Vector3<f64>, x: f64, y: f64) -> f64 {
+    (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+}
+
+/// Scan converts triangles into a frame with a depth buffer, in bands of rows running in parallel.
+pub fn rasterize(triangles: &[Triangle], width: usize, height: usize, sampling: Sampling) -> Frame {
+    let mut pixels = vec![Vector4::zeros(); width * height];
+    if width == 0 || height == 0 {
+        return Frame {
+            width,
+            height,
+            pixels,
+        };
+    }
+
+    let bands = height.div_ceil(BAND);
+    let mut bins = vec![Vec::new(); bands];
+    for (index, triangle) in triangles.iter().enumerate() {
+        let y_min = triangle
+            .vertices
+            .iter()
+            .map(|vertex| vertex.position.y)
+            .fold(f64::INFINITY, f64::min);
+        let y_max = triangle
+            .vertices
+            .iter()
+            .map(|vertex| vertex.position.y)
+            .fold(f64::NEG_INFINITY, f64::max);
+        if !(y_max >= 0.0 && y_min < height as f64) {
+            continue;
+        }
+        let first = (y_min.max(0.0) as usize / BAND).min(bands - 1);
+        let last = (y_max.min(height as f64 - 1.0) as usize / BAND).min(bands - 1);
+        for bin in &mut bins[first..=last] {
+            bin.push(index);
+        }
+    }
+
+    pixels
+        .par_chunks_mut(width * BAND)
+        .zip(bins.par_iter())
+        .enumerate()
+        .for_each(|(band, (rows, bin))| {
+            let top = band * BAND;
+            let rows_in_band = rows.len() / width;
+            // inverse depth, larger is nearer
+            let mut depth = vec![0.0; rows.len()];
+            for &index in bin {
+                let Triangle { vertices, texture } = &triangles[index];
+                let [a, b, c] = vertices;
+                let (pa, pb, pc) = (&a.position, &b.position, &c.position);
+                let area = edge(pa, pb, pc.x, pc.y);
+                if area.abs() < 1e-12 {
+                    continue;
+                }
+                let x_min = pa.x.min(pb.x).min(pc.x).floor().max(0.0) as usize;
+                let x_max = (pa.x.max(pb.x).max(pc.x).ceil().max(0.0) as usize).min(width);
+                let y_min = (pa.y.min(pb.y).min(pc.y).floor().max(top as f64) as usize)
+                    .min(top + rows_in_band);
+                let y_max =
+                    (pa.y.max(pb.y).max(pc.y).ceil().max(0.0) as usize).min(top + rows_in_band);
+                let inverse = [1.0 / pa.z, 1.0 / pb.z, 1.0 / pc.z];
+                for y in y_min..y_max {
+                    let sample_y = y as f64 + 0.5;
+                    for x in x_min..x_max {
+                        let sample_x = x as f64 + 0.5;
+                        let weights = [
+                            edge(pb, pc, sample_x, sample_y) / area,
+                            edge(pc, pa, sample_x, sample_y) / area,
+                            edge(pa, pb, sample_x, sample_y) / area,
+                        ];
+                        if weights.iter().any(|weight| *weight < 0.0) {
+                            continue;
+                        }
+                        let w = weights[0] * inverse[0]
+                            + weights[1] * inverse[1]
+                            + weights[2] * inverse[2];
+                        let pixel = (y - top) * width + x;
+                        if w <= depth[pixel] {
+                            continue;
+                        }
+                        depth[pixel] = w;
+                        // perspective correct interpolation
+                        let weights = [0, 1, 2].map(|i| weights[i] * inverse[i] / w);
+                        let interpolate = |attribute: fn(&Vertex) -> Vector3<f64>| {
+                            attribute(a) * weights[0]
+                                + attribute(b) * weights[1]
+                                + attribute(c) * weights[2]
+                        };
+                        let mut color = interpolate(|vertex| vertex.color);
+                        if let Some((texture, wrap)) = texture {
+                            let uv = a.uv * weights[0] + b.uv * weights[1] + c.uv * weights[2];
+                            let texel = texture.sample(&uv, sampling, *wrap);
+                            color += interpolate(|vertex| vertex.tint).component_mul(&texel);
+                        }
+                        rows[pixel] = color.map(|channel| channel.clamp(0.0, 1.0)).push(1.0);
+                    }
+                }
+            }
+        });
+
+    Frame {
+        width,
+        height,
+        pixels,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Filter;
+
+    fn vertex(x: f64, y: f64, z: f64, color: Vector3<f64>) -> Vertex {
+        Vertex {
+            position: Vector3::new(x, y, z),
+            color,
+            tint: Vector3::zeros(),
+            uv: Vector2::zeros(),
+        }
+    }
+
+    /// A triangle covering the whole of a small frame at depth `z`.
+    fn cover(z: f64, color: Vector3<f64>) -> Triangle<'static> {
+        Triangle {
+            vertices: [
+                vertex(-1.0, -1.0, z, color),
+                vertex(100.0, -1.0, z, color),
+                vertex(-1.0, 100.0, z, color),
+            ],
+            texture: None,
+        }
+    }
+
+    #[test]
+    fn nearest_triangle_wins_in_any_order() {
+        let (red, blue) = (Vector3::x(), Vector3::z());
+        for triangles in [
+            [cover(1.0, red), cover(2.0, blue)],
+            [cover(2.0, blue), cover(1.0, red)],
+        ] {
+            // taller than a band, so the triangles get binned into more than one
+            let frame = rasterize(&triangles, 4, BAND + 4, Sampling::Nearest);
+            assert!(frame.pixels.iter().all(|pixel| *pixel == red.push(1.0)));
+        }
+    }
+
+    #[test]
+    fn uncovered_pixels_stay_transparent() {
+        let triangle = Triangle {
+            vertices: [
+                vertex(0.0, 0.0, 1.0, Vector3::x()),
+                vertex(2.0, 0.0, 1.0, Vector3::x()),
+                vertex(0.0, 2.0, 1.0, Vector3::x()),
+            ],
+            texture: None,
+        };
+        let frame = rasterize(&[triangle], 4, 4, Sampling::Nearest);
+        assert_eq!(frame.pixels[0][3], 1.0);
+        assert_eq!(frame.pixels[3][3], 0.0);
+        assert_eq!(frame.pixels[15], Vector4::zeros());
+    }
+
+    #[test]
+    fn clips_against_the_near_plane() {
+        let color = Vector3::zeros();
+        let inside = [
+            vertex(0.0, 0.0, 1.0, color),
+            vertex(1.0, 0.0, 1.0, color),
+            vertex(0.0, 1.0, 1.0, color),
+        ];
+        assert_eq!(clip_near(&inside).len(), 3);
+
+        // one vertex behind the camera turns the triangle into a quad
+        let crossing = [
+            vertex(0.0, 0.0, 1.0, color),
+            vertex(1.0, 0.0, 1.0, color),
+            vertex(0.0, 0.0, -1.0, color),
+        ];
+        let clipped = clip_near(&crossing);
+        assert_eq!(clipped.len(), 4);
+        assert!(clipped
+            .iter()
+            .all(|vertex| vertex.position.z >= NEAR - 1e-12));
+        assert!((clipped[2].position - Vector3::new(0.5 + NEAR / 2.0, 0.0, NEAR)).norm() < 1e-12);
+
+        let behind = inside.map(|vertex| Vertex {
+            position: vertex.position - Vector3::z() * 2.0,
+            ..vertex
+        });
+        assert!(clip_near(&behind).is_empty());
+    }
+
+    /// A frame with a single row of pixels repeated `rows` times.
+    fn rows(row: &[f64], rows: usize) -> Frame {
+        Frame {
+            width: row.len(),
+            height: rows,
+            pixels: (0..rows)
+                .flat_map(|_| row.iter().map(|value| Vector4::repeat(*value)))
+                .collect(),
+        }
+    }
+
+    fn downsample(frame: &Frame, filter: Filter) -> Vec<f64> {
+        let small = frame.downsample(2, filter.radius(), |distance| filter.weight(distance));
+        assert_eq!(
+            (small.width, small.height),
+            (frame.width / 2, frame.height / 2)
+        );
+        small.pixels[..small.width]
+            .iter()
+            .map(|pixel| pixel.x)
+            .collect()
+    }
+
+    #[test]
+    fn box_filter_averages_each_block() {
+        let frame = rows(&[0.0, 1.0, 1.0, 1.0, 0.5, 0.5], 2);
+        assert_eq!(downsample(&frame, Filter::Box), vec![0.5, 1.0, 0.5]);
+    }
+
+    #[test]
+    fn tent_filter_weighs_samples_by_distance() {
+        // at twice the size, samples sit a quarter and three quarters of a pixel from a center,
+        // so the tent weighs them 0.75 and 0.25, and leaves out what's beyond the frame
+        let impulse = rows(&[0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 0.0, 0.0], 2);
+        let filtered = downsample(&impulse, Filter::Tent);
+        let expected = [0.25 / 1.75, 0.75 / 2.0, 0.0, 0.0];
+        for (value, expected) in filtered.iter().zip(expected) {
+            assert!((value - expected).abs() < 1e-12, "{:?}", filtered);
+        }
+        let flat = downsample(&rows(&[0.5; 8], 4), Filter::Tent);
+        assert!(flat.iter().all(|value| (value - 0.5).abs() < 1e-12));
+    }
+
+    #[test]
+    fn converts_premultiplied_pixels_to_bytes() {
+        let frame = Frame {
+            width: 2,
+            height: 1,
+            pixels: vec![Vector4::new(0.5, 0.0, 0.0, 0.5), Vector4::zeros()],
+        };
+        assert_eq!(frame.to_rgba8(), vec![255, 0, 0, 128, 0, 0, 0, 0]);
+        assert_eq!(frame.to_rgb8(&Vector3::z()), vec![128, 0, 128, 0, 0, 255]);
+    }
+}
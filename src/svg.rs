@@ -0,0 +1,45 @@
+use std::fmt::Write;
+
+/// An SVG document painting the polygons in order, each a list of pixel coordinates and a fill color.
+/// Without a background the image stays transparent, with a stroke every edge gets outlined.
+pub fn document(
+    width: usize,
+    height: usize,
+    background: Option<&str>,
+    stroke: Option<(&str, f64)>,
+    polygons: impl Iterator<Item = (Vec<(f64, f64)>, String)>,
+) -> String {
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" viewBox=\"0 0 {0} {1}\">\n",
+        width, height
+    );
+    if let Some(background) = background {
+        writeln!(
+            svg,
+            "<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>",
+            background
+        )
+        .unwrap();
+    }
+    match stroke {
+        Some((color, width)) => writeln!(
+            svg,
+            "<g stroke=\"{}\" stroke-width=\"{}\" stroke-linejoin=\"round\">",
+            color, width
+        ),
+        None => writeln!(svg, "<g>"),
+    }
+    .unwrap();
+    for (points, fill) in polygons {
+        svg.push_str("<polygon points=\"");
+        for (i, (x, y)) in points.iter().enumerate() {
+            if i > 0 {
+                svg.push(' ');
+            }
+            write!(svg, "{:.2},{:.2}", x, y).unwrap();
+        }
+        writeln!(svg, "\" fill=\"{}\"/>", fill).unwrap();
+    }
+    svg.push_str("</g>\n</svg>\n");
+    svg
+}
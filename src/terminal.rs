@@ -0,0 +1,118 @@
+use crate::colormap;
+use crate::raster::Frame;
+use nalgebra::{Vector3, Vector4};
+use std::fmt::Write;
+
+fn straight(pixel: &Vector4<f64>) -> Vector3<f64> {
+    pixel.xyz() / pixel[3]
+}
+
+fn rgb(color: &Vector3<f64>) -> [u8; 3] {
+    [0, 1, 2].map(|i| (color[i].clamp(0.0, 1.0) * 255.0).round() as u8)
+}
+
+/// One character per pixel, picked from `ramp` by brightness, darkest first.
+/// Pixels no polygon covers stay blank.
+pub fn ascii(frame: &Frame, ramp: &[char]) -> String {
+    let mut text = String::with_capacity((frame.width + 1) * frame.height);
+    for row in frame.pixels.chunks(frame.width.max(1)) {
+        for pixel in row {
+            if pixel[3] < 0.5 || ramp.is_empty() {
+                text.push(' ');
+                continue;
+            }
+            let shade = colormap::luminance(&straight(pixel)).clamp(0.0, 1.0);
+            text.push(ramp[(shade * (ramp.len() - 1) as f64).round() as usize]);
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// Upper half blocks colored with 24 bit ANSI escapes, two rows of pixels per line of text.
+/// Without a background, pixels no polygon covers show the terminal's own.
+pub fn blocks(frame: &Frame, background: Option<&Vector3<f64>>) -> String {
+    let width = frame.width.max(1);
+    let color = |pixel: &Vector4<f64>| match background {
+        Some(background) => Some(pixel.xyz() + background * (1.0 - pixel[3])),
+        None => (pixel[3] >= 0.5).then(|| straight(pixel)),
+    };
+    let mut text = String::new();
+    for rows in frame.pixels.chunks(2 * width) {
+        let (top, bottom) = rows.split_at(width.min(rows.len()));
+        // escapes only get written when the colors change, the terminal keeps them otherwise
+        let mut current = (None, None);
+        for (x, pixel) in top.iter().enumerate() {
+            let (glyph, foreground, background) =
+                match (color(pixel), bottom.get(x).and_then(color)) {
+                    (Some(top), bottom) => ('\u{2580}', rgb(&top), bottom.map(|c| rgb(&c))),
+                    (None, Some(bottom)) => ('\u{2584}', rgb(&bottom), None),
+                    (None, None) => (' ', [0; 3], None),
+                };
+            if current.1.is_some() && background.is_none() {
+                text.push_str("\x1b[0m");
+                current = (None, None);
+            }
+            if glyph == ' ' {
+                // a blank cell only shows the background, whatever the foreground is
+                text.push(glyph);
+                continue;
+            }
+            if Some(foreground) != current.0 {
+                let [r, g, b] = foreground;
+                write!(text, "\x1b[38;2;{};{};{}m", r, g, b).unwrap();
+            }
+            if let Some([r, g, b]) = background.filter(|_| background != current.1) {
+                write!(text, "\x1b[48;2;{};{};{}m", r, g, b).unwrap();
+            }
+            current = (Some(foreground), background);
+            text.push(glyph);
+        }
+        text.push_str("\x1b[0m\n");
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A frame out of opaque gray levels, `None` for uncovered pixels.
+    fn frame(width: usize, pixels: &[Option<Vector3<f64>>]) -> Frame {
+        Frame {
+            width,
+            height: pixels.len() / width,
+            pixels: pixels
+                .iter()
+                .map(|pixel| pixel.map_or_else(Vector4::zeros, |color| color.push(1.0)))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn ascii_picks_characters_by_brightness() {
+        let (white, black) = (Some(Vector3::repeat(1.0)), Some(Vector3::zeros()));
+        let frame = frame(3, &[white, black, None, None, white, white]);
+        let text = ascii(&frame, &['.', ':', '@']);
+        assert_eq!(text, "@. \n @@\n");
+    }
+
+    #[test]
+    fn blocks_pack_two_rows_into_each_line() {
+        let (red, blue) = (Some(Vector3::x()), Some(Vector3::z()));
+        // five rows of pixels take three lines, the last one only has a top half
+        let frame = frame(
+            2,
+            &[red, None, blue, None, None, blue, None, None, red, red],
+        );
+        let text = blocks(&frame, None);
+        let lines = text.lines().collect::<Vec<&str>>();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[0],
+            "\x1b[38;2;255;0;0m\x1b[48;2;0;0;255m\u{2580}\x1b[0m \x1b[0m"
+        );
+        assert_eq!(lines[1], " \x1b[38;2;0;0;255m\u{2580}\x1b[0m");
+        assert_eq!(lines[2], "\x1b[38;2;255;0;0m\u{2580}\u{2580}\x1b[0m");
+    }
+}
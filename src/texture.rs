@@ -0,0 +1,224 @@
+use crate::{Sampling, Wrap};
+use nalgebra::{Vector2, Vector3};
+use std::io::{self, BufReader};
+use std::path::Path;
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// An image mapped onto polygons, with colors between 0 and 1 row by row from the top left.
+pub struct Texture {
+    width: usize,
+    height: usize,
+    texels: Vec<Vector3<f64>>,
+}
+
+impl Texture {
+    /// Loads a PNG or a binary or plain PPM, going by the contents rather than the extension.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let bytes = std::fs::read(path)?;
+        if bytes.starts_with(b"\x89PNG") {
+            Self::from_png(&bytes)
+        } else if bytes.starts_with(b"P6") || bytes.starts_with(b"P3") {
+            Self::from_ppm(&bytes)
+        } else {
+            Err(invalid("textures have to be PNG or PPM images"))
+        }
+    }
+
+    fn from_png(bytes: &[u8]) -> io::Result<Self> {
+        let mut decoder = png::Decoder::new(BufReader::new(bytes));
+        // palettes, grayscale and 16 bit channels all end up as 8 bit gray or color
+        decoder.set_transformations(png::Transformations::normalize_to_color8());
+        let mut reader = decoder
+            .read_info()
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let info = reader
+            .next_frame(&mut buffer)
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))?;
+        let channels = info.color_type.samples();
+        let texels = buffer[..info.buffer_size()]
+            .chunks_exact(channels)
+            .map(|texel| {
+                let channel = |i: usize| texel[if channels < 3 { 0 } else { i }] as f64 / 255.0;
+                Vector3::new(channel(0), channel(1), channel(2))
+            })
+            .collect();
+        Ok(Self {
+            width: info.width as usize,
+            height: info.height as usize,
+            texels,
+        })
+    }
+
+    fn from_ppm(bytes: &[u8]) -> io::Result<Self> {
+        // the header is the magic number, width, height and the largest value,
+        // separated by whitespace with comments running to the end of a line
+        let mut header = Vec::new();
+        let mut position = 2;
+        while header.len() < 3 {
+            match bytes.get(position) {
+                Some(b'#') => {
+                    while bytes.get(position).is_some_and(|byte| *byte != b'\n') {
+                        position += 1;
+                    }
+                }
+                Some(byte) if byte.is_ascii_whitespace() => position += 1,
+                Some(_) => {
+                    let start = position;
+                    while bytes
+                        .get(position)
+                        .is_some_and(|byte| !byte.is_ascii_whitespace())
+                    {
+                        position += 1;
+                    }
+                    let value = std::str::from_utf8(&bytes[start..position])
+                        .ok()
+                        .and_then(|value| value.parse::<usize>().ok())
+                        .ok_or_else(|| invalid("malformed PPM header"))?;
+                    header.push(value);
+                }
+                None => return Err(invalid("truncated PPM header")),
+            }
+        }
+        let (width, height, max) = (header[0], header[1], header[2]);
+        if max == 0 || max > 65535 {
+            return Err(invalid("PPM values have to be between 1 and 65535"));
+        }
+        // exactly one whitespace character separates the header from binary data
+        let data = bytes.get(position + 1..).unwrap_or_default();
+        let values = if bytes.starts_with(b"P6") {
+            if max < 256 {
+                data.iter()
+                    .map(|value| *value as usize)
+                    .collect::<Vec<usize>>()
+            } else {
+                data.chunks_exact(2)
+                    .map(|value| (value[0] as usize) << 8 | value[1] as usize)
+                    .collect()
+            }
+        } else {
+            std::str::from_utf8(data)
+                .map_err(|_| invalid("malformed PPM data"))?
+                .split_whitespace()
+                .map(|value| value.parse::<usize>())
+                .collect::<Result<Vec<usize>, _>>()
+                .map_err(|_| invalid("malformed PPM data"))?
+        };
+        // the header is untrusted, a huge size must not wrap around into a small one
+        let size = width
+            .checked_mul(height)
+            .filter(|size| size.checked_mul(3).is_some())
+            .ok_or_else(|| invalid("PPM dimensions are too large"))?;
+        if values.len() < size * 3 {
+            return Err(invalid("truncated PPM data"));
+        }
+        let texels = values
+            .chunks_exact(3)
+            .take(size)
+            .map(|texel| Vector3::new(texel[0], texel[1], texel[2]).map(|v| v as f64 / max as f64))
+            .collect();
+        Ok(Self {
+            width,
+            height,
+            texels,
+        })
+    }
+
+    fn texel(&self, x: isize, y: isize, wrap: Wrap) -> Vector3<f64> {
+        let (x, y) = match wrap {
+            Wrap::Repeat => (
+                x.rem_euclid(self.width as isize),
+                y.rem_euclid(self.height as isize),
+            ),
+            Wrap::Clamp => (
+                x.clamp(0, self.width as isize - 1),
+                y.clamp(0, self.height as isize - 1),
+            ),
+        };
+        self.texels[y as usize * self.width + x as usize]
+    }
+
+    /// The color at texture coordinates `uv`, with v pointing up like in OBJ files.
+    pub fn sample(&self, uv: &Vector2<f64>, sampling: Sampling, wrap: Wrap) -> Vector3<f64> {
+        if self.texels.is_empty() {
+            return Vector3::repeat(1.0);
+        }
+        let x = uv[0] * self.width as f64;
+        let y = (1.0 - uv[1]) * self.height as f64;
+        match sampling {
+            Sampling::Nearest => self.texel(x.floor() as isize, y.floor() as isize, wrap),
+            Sampling::Bilinear => {
+                // texel centers sit at half coordinates
+                let (x, y) = (x - 0.5, y - 0.5);
+                let (left, top) = (x.floor(), y.floor());
+                let (tx, ty) = (x - left, y - top);
+                let (left, top) = (left as isize, top as isize);
+                let upper = self
+                    .texel(left, top, wrap)
+                    .lerp(&self.texel(left + 1, top, wrap), tx);
+                let lower = self
+                    .texel(left, top + 1, wrap)
+                    .lerp(&self.texel(left + 1, top + 1, wrap), tx);
+                upper.lerp(&lower, ty)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_ppm_with_comments_and_maxval() {
+        let ppm = b"P3 # a comment\n2 1\n# another one\n15\n15 0 0  0 5 15\n";
+        let texture = Texture::from_ppm(ppm).unwrap();
+        assert_eq!((texture.width, texture.height), (2, 1));
+        assert_eq!(
+            texture.texels,
+            vec![
+                Vector3::new(1.0, 0.0, 0.0),
+                Vector3::new(0.0, 1.0 / 3.0, 1.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_binary_ppm_with_wide_values() {
+        let mut ppm = b"P6\n1 1\n65535\n".to_vec();
+        ppm.extend_from_slice(&[0xff, 0xff, 0x00, 0x00, 0x80, 0x00]);
+        let texture = Texture::from_ppm(&ppm).unwrap();
+        assert_eq!(texture.texels[0].x, 1.0);
+        assert_eq!(texture.texels[0].y, 0.0);
+        assert!((texture.texels[0].z - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn rejects_truncated_and_malformed_ppm() {
+        let message = |ppm: &[u8]| Texture::from_ppm(ppm).err().unwrap().to_string();
+        assert_eq!(message(b"P6\n2 2"), "truncated PPM header");
+        assert_eq!(message(b"P6\n2 2\n255\n\0\0\0"), "truncated PPM data");
+        assert_eq!(message(b"P3\n1 x\n255\n"), "malformed PPM header");
+        assert_eq!(
+            message(b"P3\n1 1\n0\n"),
+            "PPM values have to be between 1 and 65535"
+        );
+        assert_eq!(
+            message(format!("P6\n{} {}\n255\n", usize::MAX / 2, 3).as_bytes()),
+            "PPM dimensions are too large"
+        );
+    }
+
+    #[test]
+    fn samples_with_wrapping() {
+        let texture = Texture::from_ppm(b"P3 2 1 1 0 0 0 1 1 1").unwrap();
+        let sample =
+            |u: f64, wrap: Wrap| texture.sample(&Vector2::new(u, 0.5), Sampling::Nearest, wrap);
+        assert_eq!(sample(0.25, Wrap::Repeat), Vector3::zeros());
+        assert_eq!(sample(1.25, Wrap::Repeat), Vector3::zeros());
+        assert_eq!(sample(1.25, Wrap::Clamp), Vector3::repeat(1.0));
+    }
+}